@@ -1,12 +1,12 @@
-use float_cmp::approx_eq;
-use std::collections::BTreeSet;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::io::{BufRead, BufReader};
 
 pub const PRINT_DEBUG: bool = false;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Point {
     x: usize,
     y: usize,
@@ -27,15 +27,12 @@ impl Starmap {
         }
     }
 
-    pub fn get_pos(&self, p: &Point) -> char {
-        self.v[p.y * self.w + p.x]
-    }
-
-    pub fn load_from_file(&mut self, file: &str) {
-        let f = BufReader::new(File::open(file).expect("Unable to open file"));
-
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
+    pub fn parse(&mut self, lines: &[String]) {
+        for line in lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|l| !l.is_empty())
+        {
             let mut v: Vec<_> = line.chars().collect();
             self.v.append(&mut v);
             self.w = line.len();
@@ -43,6 +40,15 @@ impl Starmap {
         }
     }
 
+    pub fn load_from_file(&mut self, file: &str) {
+        let f = BufReader::new(File::open(file).expect("Unable to open file"));
+        let lines: Vec<String> = f
+            .lines()
+            .map(|line| line.expect("Unable to read line"))
+            .collect();
+        self.parse(&lines);
+    }
+
     pub fn count_comets(&self) -> usize {
         let mut out = 0;
         for c in &self.v {
@@ -53,11 +59,22 @@ impl Starmap {
         out
     }
 
+    // Every asteroid, row by row
+    pub fn comets(&self) -> Vec<Point> {
+        (0..self.w * self.h)
+            .filter(|i| self.v[*i] == '#')
+            .map(|i| Point {
+                x: i % self.w,
+                y: i / self.w,
+            })
+            .collect()
+    }
+
     pub fn destroy_comet(&mut self, p: &Point) {
         self.v[p.y * self.w + p.x] = '.';
     }
 
-    pub fn print_highlighted_comet(&mut self, p: &Point) {
+    pub fn print_highlighted_comet(&self, p: &Point) {
         const CL_RED: &str = "\x1B[34m";
         const CL_FG: &str = "\x1B[0m";
         for i in 0..self.w * self.h {
@@ -65,7 +82,7 @@ impl Starmap {
                 println!()
             }
             if i == p.y * self.w + p.x {
-                print!("{}{}{}", CL_RED, '#', CL_FG);
+                print!("{}#{}", CL_RED, CL_FG);
             } else {
                 print!("{}", self.v[i]);
             }
@@ -116,142 +133,189 @@ fn mdist(p1: &Point, p2: &Point) -> usize {
     ((p2.x as isize - p1.x as isize).abs() + (p2.y as isize - p1.y as isize).abs()) as usize
 }
 
-fn main() {
-    let mut map: Starmap = Starmap::new();
-    map.load_from_file("input.txt");
-
-    let mut max_p: Point = Point { x: 0, y: 0 };
-    let mut max_comets: usize = 0;
-    let mut final_uniq_angs = BTreeSet::new();
-    let mut final_angs = Vec::new();
-
-    for x1 in 0..map.w {
-        for y1 in 0..map.h {
-            let p1 = Point { x: x1, y: y1 };
-
-            if map.get_pos(&p1) != '#' {
-                continue;
-            }
+// Quarter turns clockwise from straight up to `d', y grows downwards. Each quadrant includes its
+// first axis and excludes the next one.
+fn quadrant((dx, dy): (isize, isize)) -> usize {
+    match (dx.signum(), dy.signum()) {
+        (0, -1) | (1, -1) => 0,
+        (1, 0) | (1, 1) => 1,
+        (0, 1) | (-1, 1) => 2,
+        _ => 3,
+    }
+}
 
-            let mut uniq_angs = BTreeSet::new();
-            let mut angs = Vec::new();
+// Orders directions clockwise starting straight up, as the laser turns. Two directions in the same
+// quadrant are less than half a turn apart, so the sign of their cross product says which is first.
+fn clockwise(a: (isize, isize), b: (isize, isize)) -> Ordering {
+    quadrant(a).cmp(&quadrant(b)).then_with(|| {
+        let cross = a.0 * b.1 - a.1 * b.0;
+        0.cmp(&cross)
+    })
+}
 
-            for x2 in 0..map.w {
-                for y2 in 0..map.h {
-                    let p2 = Point { x: x2, y: y2 };
+// The other asteroids grouped by reduced direction from `station', nearest first in every group
+fn lines_of_sight(map: &Starmap, station: &Point) -> HashMap<(isize, isize), Vec<Point>> {
+    let mut out: HashMap<(isize, isize), Vec<Point>> = HashMap::new();
+    for comet in map.comets().into_iter().filter(|c| c != station) {
+        let dir = reduce((
+            comet.x as isize - station.x as isize,
+            comet.y as isize - station.y as isize,
+        ));
+        out.entry(dir).or_default().push(comet);
+    }
+    for comets in out.values_mut() {
+        comets.sort_unstable_by_key(|c| mdist(station, c));
+    }
+    out
+}
 
-                    if map.get_pos(&p2) != '#' {
-                        continue;
-                    }
+// The asteroid that sees the most others, and how many it sees
+fn best_station(map: &Starmap) -> Option<(Point, usize)> {
+    let mut best: Option<(Point, usize)> = None;
+    for station in map.comets() {
+        let seen = lines_of_sight(map, &station).len();
+        if PRINT_DEBUG {
+            println!("({}, {}) sees {}", station.x, station.y, seen);
+        }
+        if best.is_none_or(|(_, max)| seen > max) {
+            best = Some((station, seen));
+        }
+    }
+    best
+}
 
-                    if x1 == x2 && y1 == y2 {
-                        continue;
-                    }
+// Every other asteroid in the order the laser at `station' vaporises them. Each turn takes the
+// nearest remaining asteroid in every direction, so the n:th asteroid in a direction goes on turn n.
+fn vaporisation_order(map: &Starmap, station: &Point) -> Vec<Point> {
+    let mut lines: Vec<((isize, isize), Vec<Point>)> =
+        lines_of_sight(map, station).into_iter().collect();
+    lines.sort_unstable_by(|(a, _), (b, _)| clockwise(*a, *b));
+    let mut order: Vec<(usize, usize, Point)> = Vec::new(); // (turn, direction, asteroid)
+    for (dir, (_, comets)) in lines.into_iter().enumerate() {
+        for (turn, comet) in comets.into_iter().enumerate() {
+            order.push((turn, dir, comet));
+        }
+    }
+    order.sort_unstable_by_key(|(turn, dir, _)| (*turn, *dir));
+    order.into_iter().map(|(_, _, comet)| comet).collect()
+}
 
-                    let ang =
-                        reduce(((x2 as isize) - (x1 as isize), (y2 as isize) - (y1 as isize)));
-                    if PRINT_DEBUG {
-                        println!(
-                            "angle ({}, {}) -> ({}, {}): {:?} deg",
-                            p1.x, p1.y, p2.x, p2.y, ang
-                        );
-                    }
-                    uniq_angs.insert(ang);
-                    angs.push((mdist(&p1, &p2), ang, (p2.x, p2.y))); // push all comets
-                }
-            }
+// Shows the asteroids disappearing one by one
+fn animate(map: &mut Starmap, order: &[Point]) {
+    for (i, comet) in order.iter().enumerate() {
+        print!("\x1B[2J\x1B[H");
+        map.print_highlighted_comet(comet);
+        println!("Vaporizing comet #{} @ {:?}", i + 1, comet);
+        std::io::stdout().flush().ok();
+        map.destroy_comet(comet);
+        std::thread::sleep(std::time::Duration::from_millis(30));
+    }
+}
 
-            if PRINT_DEBUG {
-                println!("uniq_angs: ({}) {:?}\n", uniq_angs.len(), uniq_angs);
-            }
+fn example(s: &str) -> Starmap {
+    let mut map = Starmap::new();
+    map.parse(
+        &s.split_whitespace()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>(),
+    );
+    map
+}
 
-            if uniq_angs.len() > max_comets {
-                max_p = p1;
-                max_comets = uniq_angs.len();
-                final_uniq_angs = uniq_angs;
-                final_angs = angs;
-            }
+// Usage: day10 [--animate]
+fn main() {
+    {
+        // Asserts
+        let dirs = [
+            (0, -1),
+            (1, -2),
+            (1, -1),
+            (2, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+        for pair in dirs.windows(2) {
+            assert_eq!(clockwise(pair[0], pair[1]), Ordering::Less);
+            assert_eq!(clockwise(pair[1], pair[0]), Ordering::Greater);
         }
+        assert_eq!(clockwise((2, 1), (2, 1)), Ordering::Equal);
+
+        let map = example(".#..# ..... ##### ....# ...##");
+        assert_eq!(best_station(&map), Some((Point { x: 3, y: 4 }, 8)));
+
+        let map = example(
+            ".#....#####...#.. ##...##.#####..## ##...#...#.#####. ..#.....#...###.. ..#.#.....#....##",
+        );
+        let order = vaporisation_order(&map, &Point { x: 8, y: 3 });
+        let first: Vec<(usize, usize)> = order.iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(
+            first,
+            vec![
+                (8, 1),
+                (9, 0),
+                (9, 1),
+                (10, 0),
+                (9, 2),
+                (11, 1),
+                (12, 1),
+                (11, 2),
+                (15, 1),
+                (12, 2),
+                (13, 2),
+                (14, 2),
+                (15, 2),
+                (12, 3),
+                (16, 4),
+                (15, 4),
+                (10, 4),
+                (4, 4),
+                (2, 4),
+                (2, 3),
+                (0, 2),
+                (1, 2),
+                (0, 1),
+                (1, 1),
+                (5, 2),
+                (1, 0),
+                (5, 1),
+                (6, 1),
+                (6, 0),
+                (7, 0),
+                (8, 0),
+                (10, 1),
+                (14, 0),
+                (16, 1),
+                (13, 3),
+                (14, 3),
+            ]
+        );
+        assert_eq!(order.len(), map.count_comets() - 1);
     }
 
+    let mut map: Starmap = Starmap::new();
+    map.load_from_file("input.txt");
     println!("w: {}, h: {}", map.w, map.h);
     println!("nr comets: {}", map.count_comets());
-    println!(
-        "max: ({}, {}) @ {} comets",
-        max_p.x,
-        max_p.y,
-        final_uniq_angs.len()
-    );
 
-    final_angs.sort_unstable_by_key(|(d, _, _)| *d);
-
-    println!("all other comets: {:?}", final_angs);
-
-    print!("Scanner station deployed at:");
-    map.print_highlighted_comet(&max_p);
-
-    // Part 2
-    let mut interactive = true;
-    let num_starting_comets = map.count_comets();
-
-    let mut all_angles = Vec::new();
-    for (dist, (angx, angy), (posx, posy)) in final_angs {
-        let angle = (angx as f64).atan2(-angy as f64);
-        let angle = if angle < 0f64 {
-            angle + 2.0f64 * std::f64::consts::PI
-        } else {
-            angle
-        };
-        let angle = angle * 360f64 / 2.0f64 / std::f64::consts::PI;
-        let ang = (angle, (angx, angy), dist, (posx, posy), false); // last bool == is_destroyed
-        all_angles.push(ang);
-    }
-
-    all_angles.sort_by_key(|(_, _, a, _, _)| *a); // sort by dist
-    all_angles.sort_by(|(a, _, _, _, _), (b, _, _, _, _)| a.partial_cmp(b).unwrap()); // sort by angle
-
-    for ang in &all_angles {
-        println!("{:?}", ang);
+    let (station, seen) = best_station(&map).expect("No asteroids");
+    {
+        // Part 1
+        println!("max: ({}, {}) @ {} comets", station.x, station.y, seen);
+        println!("Part 1: Ans is: {}", seen);
     }
-
-    let mut last_realang = 360f64;
-    let mut i = 0;
-    while map.count_comets() > 1 {
-        if i == all_angles.len() {
-            i = 0;
-        }
-        let (realang, (_, _), _, (x, y), destroyed) = &mut all_angles[i];
-        let comet = Point { x: *x, y: *y };
-        if approx_eq!(f64, last_realang, *realang, ulps = 2) {
-            i += 1;
-            continue;
+    {
+        // Part 2
+        let order = vaporisation_order(&map, &station);
+        if std::env::args().any(|arg| arg == "--animate") {
+            animate(&mut map, &order);
         }
-        if *destroyed == true {
-            i += 1;
-            continue;
-        }
-
-        map.print_highlighted_comet(&comet);
-        if interactive {
-            println!(
-                "Press enter to vaporize comet #{} @ {:?}",
-                num_starting_comets - map.count_comets() + 1,
-                comet
-            );
-            print!("or send RUN to exit interactive mode > ");
-            std::io::stdout().flush().ok();
-            let mut line = String::new();
-            let _input = std::io::stdin()
-                .read_line(&mut line)
-                .expect("Failed to read line");
-            if line == "RUN\n" {
-                interactive = false;
-            }
-        }
-
-        last_realang = *realang;
-        map.destroy_comet(&comet);
-        *destroyed = true;
-        i += 1;
+        print!("Scanner station deployed at:");
+        map.print_highlighted_comet(&station);
+        let last = order.get(199).expect("Fewer than 200 comets to vaporize");
+        println!("Part 2: Ans is: {}", last.x * 100 + last.y);
     }
 }
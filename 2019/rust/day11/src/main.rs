@@ -1,6 +1,5 @@
-pub mod machine;
-use machine::*;
-// use std::io::Write;
+use intcode::machine::*;
+use std::collections::{HashMap, HashSet};
 
 const BLACK: usize = 0;
 const WHITE: usize = 1;
@@ -8,21 +7,8 @@ const BLACK_CHAR: char = '.';
 const WHITE_CHAR: char = '#';
 const TURN_LEFT: usize = 0;
 const TURN_RIGHT: usize = 1;
-const WIDTH: usize = 100;
-const HEIGHT: usize = 100;
-const LENGTH: usize = WIDTH * HEIGHT;
-const WALK_HOR: usize = 1;
-const WALK_VER: usize = WIDTH;
-
-const fn xy_to_pos(x: usize, y: usize) -> usize {
-    y * WIDTH + x
-}
-
-const fn _pos_to_xy(pos: usize) -> (usize, usize) {
-    (pos % WIDTH, pos / WIDTH)
-}
 
-const START: usize = xy_to_pos(60, 60);
+type Coord = (isize, isize); // x grows to the right and y downwards
 
 #[derive(Copy, Clone, PartialEq, Debug)] // Debug printable
 enum Direction {
@@ -34,21 +20,88 @@ enum Direction {
 
 #[derive(Copy, Clone, PartialEq, Debug)] // Debug printable
 struct Painter {
-    pos: usize,
+    pos: Coord,
     dir: Direction,
 }
 
-fn painter_operate(
-    painter: &mut Painter,
-    canvas: &mut [char; LENGTH],
-    color: usize,
-    new_dir: usize,
-) {
-    canvas[painter.pos] = match color {
-        BLACK => BLACK_CHAR,
-        WHITE => WHITE_CHAR,
-        _ => BLACK_CHAR,
-    };
+// The hull is unbounded, panels never painted are black
+#[derive(Clone, PartialEq, Debug)]
+struct Hull {
+    panels: HashMap<Coord, usize>, // Panel -> colour, only panels that have been painted or set
+    painted: HashSet<Coord>,       // Panels the robot painted at least once, in any colour
+}
+
+impl Hull {
+    fn new() -> Hull {
+        Hull {
+            panels: HashMap::new(),
+            painted: HashSet::new(),
+        }
+    }
+
+    fn colour(&self, pos: Coord) -> usize {
+        *self.panels.get(&pos).unwrap_or(&BLACK)
+    }
+
+    fn paint(&mut self, pos: Coord, colour: usize) {
+        self.panels.insert(pos, colour);
+        self.painted.insert(pos);
+    }
+
+    // Smallest and largest corner of the white panels
+    fn bounds(&self) -> Option<(Coord, Coord)> {
+        let white: Vec<&Coord> = self
+            .panels
+            .iter()
+            .filter(|(_, colour)| **colour == WHITE)
+            .map(|(pos, _)| pos)
+            .collect();
+        let minx = white.iter().map(|p| p.0).min()?;
+        let maxx = white.iter().map(|p| p.0).max()?;
+        let miny = white.iter().map(|p| p.1).min()?;
+        let maxy = white.iter().map(|p| p.1).max()?;
+        Some(((minx, miny), (maxx, maxy)))
+    }
+
+    // Rows of the hull cropped to the white panels, true is white
+    fn bitmap(&self) -> Vec<Vec<bool>> {
+        match self.bounds() {
+            Some(((minx, miny), (maxx, maxy))) => (miny..=maxy)
+                .map(|y| {
+                    (minx..=maxx)
+                        .map(|x| self.colour((x, y)) == WHITE)
+                        .collect()
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        for row in self.bitmap() {
+            for white in row {
+                out.push(if white { WHITE_CHAR } else { BLACK_CHAR });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // The registration identifier painted on the hull
+    fn identifier(&self) -> Result<String, String> {
+        ocr::read(&self.bitmap())
+    }
+}
+
+fn painter_operate(painter: &mut Painter, hull: &mut Hull, color: usize, new_dir: usize) {
+    hull.paint(
+        painter.pos,
+        match color {
+            WHITE => WHITE,
+            _ => BLACK,
+        },
+    );
     painter.dir = match new_dir {
         TURN_LEFT => match painter.dir {
             Direction::Right => Direction::Up,
@@ -64,22 +117,45 @@ fn painter_operate(
         },
         _ => Direction::Up,
     };
+    let (x, y) = painter.pos;
     painter.pos = match painter.dir {
-        Direction::Right => painter.pos + WALK_HOR,
-        Direction::Up => painter.pos - WALK_VER,
-        Direction::Left => painter.pos - WALK_HOR,
-        Direction::Down => painter.pos + WALK_VER,
+        Direction::Right => (x + 1, y),
+        Direction::Up => (x, y - 1),
+        Direction::Left => (x - 1, y),
+        Direction::Down => (x, y + 1),
     }
 }
 
-fn print_canvas(canvas: [char; LENGTH]) {
-    for i in 0..canvas.len() {
-        if i % WIDTH == 0 {
-            println!();
+// Runs the robot program until it halts, starting on a panel of colour `start'. The robot reads
+// a colour and answers with a (colour, turn) pair every time, anything else is an error.
+fn paint_hull(program: &Machine, start: usize) -> Result<Hull, String> {
+    let mut m: Machine = Machine::new();
+    m.memcpy(program);
+    let mut painter = Painter {
+        pos: (0, 0),
+        dir: Direction::Up,
+    };
+    let mut hull = Hull::new();
+    hull.panels.insert(painter.pos, start);
+
+    loop {
+        m.put_input(hull.colour(painter.pos) as isize);
+        let result = run_machine(&mut m);
+        if !m.output_waiting().is_multiple_of(2) {
+            return Err(format!(
+                "The robot gave {} outputs, not (colour, turn) pairs",
+                m.output_waiting()
+            ));
+        }
+        while let (Some(color), Some(new_dir)) = (m.get_output(), m.get_output()) {
+            painter_operate(&mut painter, &mut hull, color as usize, new_dir as usize);
+        }
+        match result {
+            RunResult::Halted => return Ok(hull),
+            RunResult::WaitingForInput => (),
+            _ => return Err(format!("The robot stopped without halting: {:?}", result)),
         }
-        print!("{}", canvas[i]);
     }
-    println!();
 }
 
 fn main() {
@@ -90,52 +166,72 @@ fn main() {
     let ints = load_machine_from_file(&mut m, "input.txt");
     println!("Num ints read = {}", ints);
 
+    {
+        // Part 1
+        let hull = paint_hull(&m, BLACK).unwrap();
+        println!("Part 1: Ans is: {}", hull.painted.len());
+    }
+    {
+        // Part 2
+        let hull = paint_hull(&m, WHITE).unwrap();
+        print!("{}", hull.render());
+        println!("Part 2: Ans is: {}", hull.identifier().unwrap());
+    }
+}
+
+fn run_asserts() {
+    // The example from the puzzle, (colour, turn) pairs
     let mut painter = Painter {
-        pos: START,
+        pos: (0, 0),
         dir: Direction::Up,
     };
-    let mut canvas = [' '; LENGTH];
-    canvas[START] = '#';
-
-    m.put_input(match canvas[painter.pos] {
-        BLACK_CHAR => BLACK,
-        WHITE_CHAR => WHITE,
-        _ => BLACK,
-    } as isize);
-
-    loop {
-        run_machine(&mut m);
-        if m.is_halted() {
-            break;
+    let mut hull = Hull::new();
+    for (color, new_dir) in [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)].iter() {
+        painter_operate(&mut painter, &mut hull, *color, *new_dir);
+    }
+    assert_eq!(hull.painted.len(), 6);
+    assert_eq!(hull.colour((0, 0)), BLACK); // Painted black still counts as painted
+    assert_eq!(painter.pos, (0, -1));
+    assert_eq!(painter.dir, Direction::Left);
+    assert_eq!(hull.render(), "..#\n..#\n##.\n");
+
+    // A staircase up and to the left, well off where the old fixed canvas ended
+    let mut hull = Hull::new();
+    let mut painter = Painter {
+        pos: (0, 0),
+        dir: Direction::Up,
+    };
+    for i in 0..1000 {
+        let turn = if i % 2 == 0 { TURN_LEFT } else { TURN_RIGHT };
+        painter_operate(&mut painter, &mut hull, WHITE, turn);
+    }
+    assert_eq!(painter.pos, (-500, -500));
+    assert_eq!(hull.bounds(), Some(((-500, -499), (0, 0))));
+    assert_eq!(hull.painted.len(), 1000);
+
+    // Reading text back, also from a hull where it is painted away from the origin
+    let rows = ocr::draw("JRZBLGKH").unwrap();
+    assert_eq!(ocr::read(&rows), Ok("JRZBLGKH".to_string()));
+    let mut hull = Hull::new();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, white) in row.iter().enumerate() {
+            hull.paint((x as isize - 20, y as isize - 3), *white as usize);
         }
-
-        let color = m.get_output().unwrap();
-        let new_dir = m.get_output().unwrap();
-        painter_operate(&mut painter, &mut canvas, color as usize, new_dir as usize);
-
-        m.put_input(match canvas[painter.pos] {
-            BLACK_CHAR => BLACK,
-            WHITE_CHAR => WHITE,
-            _ => BLACK,
-        } as isize);
-        // print_canvas(canvas);
-        // println!();
-        // println!("Press enter to execute next command");
-        // std::io::stdout().flush().ok();
-        // let mut line = String::new();
-        // let _input = std::io::stdin()
-        //     .read_line(&mut line)
-        //     .expect("Failed to read line");
     }
-
-    print_canvas(canvas);
-    println!(
-        "num painted: {}/{}",
-        canvas.iter().filter(|&c| *c == '.' || *c == '#').count(),
-        LENGTH
-    );
-}
-
-fn run_asserts() {
-    assert!(0 == 0);
+    assert_eq!(hull.identifier(), Ok("JRZBLGKH".to_string()));
+    assert!(ocr::read(&rows[1..]).is_err());
+    let shifted: Vec<Vec<bool>> = rows.iter().map(|row| row[1..].to_vec()).collect();
+    assert!(ocr::read(&shifted).is_err());
+
+    // Robots that paint one panel white and turn left, then halt, stop early or run off the end
+    let robot = |ints: &[isize]| {
+        let mut m = Machine::new();
+        load_machine_from_slice(&mut m, ints);
+        paint_hull(&m, BLACK)
+    };
+    let hull = robot(&[3, 100, 104, 1, 104, 0, 99]).unwrap();
+    assert_eq!(hull.painted.len(), 1);
+    assert_eq!(hull.colour((0, 0)), WHITE);
+    assert!(robot(&[3, 100, 104, 1, 99]).is_err());
+    assert!(robot(&[3, 100, 104, 1, 104, 0]).is_err());
 }
@@ -1,19 +1,18 @@
 use regex::Regex;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::hash_set::HashSet;
 use std::fs::File;
-use std::hash::Hasher;
-use std::io::Write;
 use std::io::{BufRead, BufReader};
-extern crate ctrlc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct Body {
-    name: String,
-    pos: [isize; 3],
-    vel: [isize; 3],
+    pos: Vec<isize>, // One coordinate per dimension
+    vel: Vec<isize>,
+}
+
+impl Body {
+    fn new(pos: Vec<isize>) -> Self {
+        let vel = vec![0; pos.len()];
+        Body { pos, vel }
+    }
 }
 
 fn load_from_file(bodies: &mut Vec<Body>, file: &str) {
@@ -21,195 +20,110 @@ fn load_from_file(bodies: &mut Vec<Body>, file: &str) {
     let f = File::open(file).expect("Unable to open file");
     let f = BufReader::new(f);
 
-    // Scan the lines of the file
+    // Scan the lines of the file, every `name=value' is one dimension
     for line in f.lines() {
         let line = line.expect("Unable to read line");
-        let mut caps = re.captures_iter(&line);
-
-        bodies.push(Body {
-            name: "".to_string(),
-            pos: [
-                caps.next().unwrap()[1].parse().unwrap(),
-                caps.next().unwrap()[1].parse().unwrap(),
-                caps.next().unwrap()[1].parse().unwrap(),
-            ],
-            vel: [0; 3],
-        });
+        let pos: Vec<isize> = re
+            .captures_iter(&line)
+            .map(|cap| cap[1].parse().unwrap())
+            .collect();
+        if !pos.is_empty() {
+            bodies.push(Body::new(pos));
+        }
     }
 }
 
-fn simulate(bodies: &mut Vec<Body>) {
+// Gravity pulls each coordinate one step towards every other body's coordinate
+fn gravity(pos: isize, other: isize) -> isize {
+    (other - pos).signum()
+}
+
+fn simulate(bodies: &mut [Body]) {
     // Apply gravity
     for i in 0..bodies.len() {
-        for k in 0..3 {
+        for k in 0..bodies[i].pos.len() {
             for j in 0..bodies.len() {
-                if i == j {
-                    continue;
-                }
-                if bodies[i].pos[k] < bodies[j].pos[k] {
-                    bodies[i].vel[k] += 1;
-                    // println!(
-                    //     "{}({}) < {}({})  (+1)",
-                    //     bodies[i].name, bodies[i].pos[k], bodies[j].name, bodies[j].pos[k],
-                    // )
-                }
-                if bodies[i].pos[k] > bodies[j].pos[k] {
-                    bodies[i].vel[k] -= 1;
-                    // println!(
-                    //     "{}({}) > {}({})  (-1)",
-                    //     bodies[i].name, bodies[i].pos[k], bodies[j].name, bodies[j].pos[k],
-                    // )
-                }
+                bodies[i].vel[k] += gravity(bodies[i].pos[k], bodies[j].pos[k]);
             }
         }
     }
 
     // Apply velocity
-    for i in 0..bodies.len() {
-        for k in 0..3 {
-            bodies[i].pos[k] += bodies[i].vel[k];
+    for body in bodies.iter_mut() {
+        for k in 0..body.pos.len() {
+            body.pos[k] += body.vel[k];
         }
     }
 }
 
 fn total_energy(body: &Body) -> usize {
-    let mut sum_abs_pos = 0;
-    let mut sum_abs_vel = 0;
-    for i in 0..3 {
-        sum_abs_pos += body.pos[i].abs() as usize;
-        sum_abs_vel += body.vel[i].abs() as usize;
-    }
+    let sum_abs_pos: usize = body.pos.iter().map(|p| p.unsigned_abs()).sum();
+    let sum_abs_vel: usize = body.vel.iter().map(|v| v.unsigned_abs()).sum();
     sum_abs_pos * sum_abs_vel
 }
 
-fn get_hash(bodies: &Vec<Body>, axis: usize) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    for body in bodies {
-        hasher.write_isize(body.pos[axis]);
-        hasher.write_isize(body.vel[axis]);
-    }
-    hasher.finish()
+fn system_energy(bodies: &[Body]) -> usize {
+    bodies.iter().map(total_energy).sum()
 }
 
-fn main() {
-    let names = ["Io", "Europa", "Ganymede", "Callisto"];
-    let mut moons = Vec::new();
-
-    load_from_file(&mut moons, "input.txt");
-    for i in 0..moons.len() {
-        moons[i].name = names[i].to_string();
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
+}
 
-    // Start the simulation
-    let mut interactive = true;
-    for moon in &moons {
-        println!("{:?}, energy: {}", moon, total_energy(&moon));
-    }
-    for i in 0..1000 {
-        if interactive {
-            println!("Press enter to execute next time step");
-            print!("or send RUN to exit interactive mode > ");
-            std::io::stdout().flush().ok();
-            let mut line = String::new();
-            let _input = std::io::stdin()
-                .read_line(&mut line)
-                .expect("Failed to read line");
-            if line == "RUN\n" {
-                interactive = false;
-            }
-        }
-
-        simulate(&mut moons);
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
 
-        if interactive {
-            println!("Step {}:", i + 1);
-            for moon in &moons {
-                println!("{:?}, energy: {}", moon, total_energy(&moon));
+// The axes do not affect each other, so each one repeats on its own. Every state has exactly one
+// predecessor, which means the first repeated state is the initial one.
+fn axis_period(bodies: &[Body], axis: usize) -> usize {
+    let initial_pos: Vec<isize> = bodies.iter().map(|body| body.pos[axis]).collect();
+    let initial_vel: Vec<isize> = bodies.iter().map(|body| body.vel[axis]).collect();
+    let mut pos = initial_pos.clone();
+    let mut vel = initial_vel.clone();
+    let mut steps = 0;
+    loop {
+        for i in 0..pos.len() {
+            for j in 0..pos.len() {
+                vel[i] += gravity(pos[i], pos[j]);
             }
-            println!(
-                "total energy: {}",
-                total_energy(&moons[0])
-                    + total_energy(&moons[1])
-                    + total_energy(&moons[2])
-                    + total_energy(&moons[3])
-            );
-            println!("");
+        }
+        for i in 0..pos.len() {
+            pos[i] += vel[i];
+        }
+        steps += 1;
+        if pos == initial_pos && vel == initial_vel {
+            return steps;
         }
     }
-    for moon in &moons {
-        println!("{:?}, energy: {}", moon, total_energy(&moon));
-    }
-    println!(
-        "total energy: {}",
-        total_energy(&moons[0])
-            + total_energy(&moons[1])
-            + total_energy(&moons[2])
-            + total_energy(&moons[3])
-    );
-    println!("");
+}
 
-    // Part 2
-    // We find the oscillation periods for each axis and each moon independently
-    moons = Vec::new();
+// Number of steps until the bodies are back where they started
+fn period(bodies: &[Body]) -> usize {
+    let dimensions = bodies.first().map_or(0, |body| body.pos.len());
+    (0..dimensions)
+        .map(|axis| axis_period(bodies, axis))
+        .fold(1, lcm)
+}
 
+fn main() {
+    let mut moons = Vec::new();
     load_from_file(&mut moons, "input.txt");
-    for i in 0..moons.len() {
-        moons[i].name = names[i].to_string();
-    }
 
-    let mut hashes = Vec::new();
-    for _ in 0..3 {
-        hashes.push(HashSet::new());
+    // Part 1
+    let mut bodies = moons.clone();
+    for _ in 0..1000 {
+        simulate(&mut bodies);
     }
+    println!("Part 1: Ans is: {}", system_energy(&bodies));
 
-    let mut cycles: [usize; 3] = [0; 3];
-    let mut last_cycles: [usize; 3] = [0; 3];
-    let mut i = 0;
-
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })
-    .expect("Error setting Ctrl-C handler");
-
-    println!("Waiting for Ctrl-C...");
-    // while running.load(Ordering::SeqCst) {
-    loop {
-        i += 1;
-        simulate(&mut moons);
-        for j in 0..3 {
-            if !hashes[j].insert(get_hash(&moons, j)) {
-                if cycles[j] == 0 {
-                    // New cycle detected
-                    cycles[j] = i - 1;
-                    last_cycles[j] = i;
-                } else {
-                    if last_cycles[j] == i - 1 {
-                        // Cycle continuation
-                        last_cycles[j] = i;
-                    } else {
-                        // Cycle broken
-                        cycles[j] = 0;
-                    }
-                }
-                // println!("Repeat in {} detected after {} iterations", j, i);
-            }
-        }
-
-        if i % 10000 == 0 {
-            println!("{}", i);
-        }
-
-        if !running.load(Ordering::SeqCst) {
-            println!("\n{} iterations, cycles: {:?}", i, cycles);
-            running.store(true, Ordering::SeqCst); // Continue iterate
-        }
-    }
-    // println!("\nRan for {} iterations, cycles: {:?}", i, cycles);
-    // println!("Repeat detected after {} iterations", i); // 4686774924
-    //                                               2782254339018719232
+    // Part 2
+    // We find the period of each axis independently, the whole system repeats at their lcm
+    println!("Part 2: Ans is: {}", period(&moons));
 }
 
 #[cfg(test)]
@@ -218,27 +132,12 @@ mod tests {
 
     #[test]
     fn test_1() {
-        let mut moons = Vec::new();
-        moons.push(Body {
-            name: "Io".to_string(),
-            pos: [-1, 0, 2],
-            vel: [0, 0, 0],
-        });
-        moons.push(Body {
-            name: "Europa".to_string(),
-            pos: [2, -10, -7],
-            vel: [0, 0, 0],
-        });
-        moons.push(Body {
-            name: "Ganymede".to_string(),
-            pos: [4, -8, 8],
-            vel: [0, 0, 0],
-        });
-        moons.push(Body {
-            name: "Callisto".to_string(),
-            pos: [3, 5, -1],
-            vel: [0, 0, 0],
-        });
+        let mut moons = vec![
+            Body::new(vec![-1, 0, 2]),
+            Body::new(vec![2, -10, -7]),
+            Body::new(vec![4, -8, 8]),
+            Body::new(vec![3, 5, -1]),
+        ];
 
         for _ in 0..10 {
             simulate(&mut moons);
@@ -262,27 +161,12 @@ mod tests {
 
     #[test]
     fn test_2() {
-        let mut moons = Vec::new();
-        moons.push(Body {
-            name: "Io".to_string(),
-            pos: [-8, -10, 0],
-            vel: [0, 0, 0],
-        });
-        moons.push(Body {
-            name: "Europa".to_string(),
-            pos: [5, 5, 10],
-            vel: [0, 0, 0],
-        });
-        moons.push(Body {
-            name: "Ganymede".to_string(),
-            pos: [2, -7, 3],
-            vel: [0, 0, 0],
-        });
-        moons.push(Body {
-            name: "Callisto".to_string(),
-            pos: [9, -8, -3],
-            vel: [0, 0, 0],
-        });
+        let mut moons = vec![
+            Body::new(vec![-8, -10, 0]),
+            Body::new(vec![5, 5, 10]),
+            Body::new(vec![2, -7, 3]),
+            Body::new(vec![9, -8, -3]),
+        ];
 
         for _ in 0..100 {
             simulate(&mut moons);
@@ -303,4 +187,55 @@ mod tests {
         assert!(total_energy(&moons[2]) == 574);
         assert!(total_energy(&moons[3]) == 468);
     }
+
+    #[test]
+    fn test_period() {
+        let moons = vec![
+            Body::new(vec![-1, 0, 2]),
+            Body::new(vec![2, -10, -7]),
+            Body::new(vec![4, -8, 8]),
+            Body::new(vec![3, 5, -1]),
+        ];
+        assert_eq!(period(&moons), 2772);
+
+        let moons = vec![
+            Body::new(vec![-8, -10, 0]),
+            Body::new(vec![5, 5, 10]),
+            Body::new(vec![2, -7, 3]),
+            Body::new(vec![9, -8, -3]),
+        ];
+        assert_eq!(period(&moons), 4686774924);
+    }
+
+    #[test]
+    fn test_other_dimensions() {
+        // Two bodies on a line swing back and forth
+        let mut bodies = vec![Body::new(vec![0]), Body::new(vec![3])];
+        let steps = period(&bodies);
+        for _ in 0..steps {
+            simulate(&mut bodies);
+        }
+        assert_eq!(bodies, vec![Body::new(vec![0]), Body::new(vec![3])]);
+    }
+
+    #[test]
+    fn test_period_in_motion() {
+        // Part way round the orbit the bodies are moving, but the cycle is the same one
+        let mut moons = vec![
+            Body::new(vec![-1, 0, 2]),
+            Body::new(vec![2, -10, -7]),
+            Body::new(vec![4, -8, 8]),
+            Body::new(vec![3, 5, -1]),
+        ];
+        for _ in 0..10 {
+            simulate(&mut moons);
+        }
+        let start = moons.clone();
+        let steps = period(&moons);
+        assert_eq!(steps, 2772);
+        for _ in 0..steps {
+            simulate(&mut moons);
+        }
+        assert_eq!(moons, start);
+    }
 }
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+pub const ORE: &str = "ORE"; // The only chemical without a reaction
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reaction {
+    pub output_amount: u64,
+    pub inputs: Vec<(u64, String)>, // Amount and name of every ingredient
+}
+
+#[derive(Debug)]
+pub struct Reactions {
+    reactions: HashMap<String, Reaction>, // Output name -> reaction producing it
+    order: Vec<String>, // Every chemical after all chemicals made from it, ends with ORE
+}
+
+// Parses `7 A' into (7, "A")
+fn parse_term(term: &str) -> Result<(u64, String), String> {
+    let mut split = term.split_whitespace();
+    let amount = split
+        .next()
+        .and_then(|amount| amount.parse().ok())
+        .filter(|amount| *amount > 0)
+        .ok_or(format!("Bad amount in `{}'", term))?;
+    let name = split.next().ok_or(format!("Missing name in `{}'", term))?;
+    if split.next().is_some() {
+        return Err(format!("Trailing text in `{}'", term));
+    }
+    Ok((amount, name.to_string()))
+}
+
+impl Reactions {
+    // One reaction per line, `7 A, 1 B => 1 C'
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut reactions = HashMap::new();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut sides = line.split("=>");
+            let (inputs, output) = match (sides.next(), sides.next(), sides.next()) {
+                (Some(inputs), Some(output), None) => (inputs, output),
+                _ => return Err(format!("Expected one `=>' in `{}'", line)),
+            };
+            let inputs = inputs
+                .split(',')
+                .map(parse_term)
+                .collect::<Result<Vec<_>, _>>()?;
+            let (output_amount, output_name) = parse_term(output)?;
+            if output_name == ORE {
+                return Err(format!("{} can not be produced, in `{}'", ORE, line));
+            }
+            let reaction = Reaction {
+                output_amount,
+                inputs,
+            };
+            if reactions.insert(output_name.clone(), reaction).is_some() {
+                return Err(format!("More than one reaction produces {}", output_name));
+            }
+        }
+        for reaction in reactions.values() {
+            for (_, name) in &reaction.inputs {
+                if name != ORE && !reactions.contains_key(name) {
+                    return Err(format!("No reaction produces {}", name));
+                }
+            }
+        }
+        let order = topological_order(&reactions)?;
+        Ok(Reactions { reactions, order })
+    }
+
+    pub fn from_file(file: &str) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        Self::parse(&text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Reaction> {
+        self.reactions.get(name)
+    }
+
+    // Ore needed, leftovers of one reaction are used by the next. None on overflow.
+    fn ore_needed(&self, amount: u64, target: &str) -> Option<u128> {
+        let mut needed: HashMap<&str, u128> = HashMap::new();
+        needed.insert(target, amount as u128);
+        // Everything using a chemical comes before it, so its total is known when we get there
+        for name in &self.order {
+            let reaction = match self.reactions.get(name) {
+                Some(reaction) => reaction,
+                None => continue, // ORE
+            };
+            let amount = match needed.get(name.as_str()) {
+                Some(amount) => *amount,
+                None => continue,
+            };
+            let times = amount.div_ceil(reaction.output_amount as u128);
+            for (in_amount, in_name) in &reaction.inputs {
+                let total = needed.entry(in_name).or_insert(0);
+                *total = total.checked_add(times.checked_mul(*in_amount as u128)?)?;
+            }
+        }
+        Some(needed.get(ORE).copied().unwrap_or(0))
+    }
+
+    fn check_target(&self, target: &str) -> Result<(), String> {
+        if target == ORE || self.reactions.contains_key(target) {
+            Ok(())
+        } else {
+            Err(format!("No reaction produces {}", target))
+        }
+    }
+
+    // Ore needed to produce `amount' of `target'
+    pub fn ore_for(&self, amount: u64, target: &str) -> Result<u64, String> {
+        self.check_target(target)?;
+        self.ore_needed(amount, target)
+            .filter(|ore| *ore <= u64::MAX as u128)
+            .map(|ore| ore as u64)
+            .ok_or(format!(
+                "Producing {} {} needs too much ore",
+                amount, target
+            ))
+    }
+
+    // The most `target' that `budget' ore can produce
+    pub fn max_from(&self, budget: u64, target: &str) -> Result<u64, String> {
+        self.check_target(target)?;
+        let affordable = |amount: u64| {
+            self.ore_needed(amount, target)
+                .is_some_and(|ore| ore <= budget as u128)
+        };
+        // Double until out of budget, then binary search between the last two
+        let mut low = 0; // Always affordable
+        let mut high = 1;
+        while affordable(high) {
+            low = high;
+            high = match high.checked_mul(2) {
+                Some(high) => high,
+                None => return Ok(u64::MAX), // Only when nothing costs any ore
+            };
+        }
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if affordable(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    // The recipe graph in Graphviz format, edges go from ingredient to product
+    pub fn to_dot(&self) -> String {
+        let mut out = "digraph reactions {\n".to_string();
+        for name in self.order.iter().rev() {
+            match self.reactions.get(name) {
+                Some(reaction) => {
+                    out += &format!(
+                        "    \"{}\" [label=\"{} {}\"];\n",
+                        name, reaction.output_amount, name
+                    );
+                    for (in_amount, in_name) in &reaction.inputs {
+                        out += &format!(
+                            "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                            in_name, name, in_amount
+                        );
+                    }
+                }
+                None => out += &format!("    \"{}\" [shape=box];\n", name),
+            }
+        }
+        out += "}\n";
+        out
+    }
+}
+
+// Kahn's algorithm over product -> ingredient edges
+fn topological_order(reactions: &HashMap<String, Reaction>) -> Result<Vec<String>, String> {
+    let mut users: HashMap<&str, usize> = HashMap::new(); // Reactions using each chemical
+    users.insert(ORE, 0);
+    for name in reactions.keys() {
+        users.entry(name).or_insert(0);
+    }
+    for reaction in reactions.values() {
+        for (_, in_name) in &reaction.inputs {
+            *users.get_mut(in_name.as_str()).unwrap() += 1;
+        }
+    }
+    let mut ready: Vec<&str> = users
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(name, _)| *name)
+        .collect();
+    ready.sort_unstable(); // Same order on every run
+    let mut ready: VecDeque<&str> = ready.into();
+    let mut order = Vec::new();
+    while let Some(name) = ready.pop_front() {
+        order.push(name.to_string());
+        if let Some(reaction) = reactions.get(name) {
+            for (_, in_name) in &reaction.inputs {
+                let count = users.get_mut(in_name.as_str()).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push_back(in_name);
+                }
+            }
+        }
+    }
+    if order.len() < users.len() {
+        let mut cycle: Vec<&str> = users
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(name, _)| *name)
+            .collect();
+        cycle.sort_unstable();
+        return Err(format!("Reactions form a cycle through {:?}", cycle));
+    }
+    Ok(order)
+}
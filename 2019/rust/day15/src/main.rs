@@ -1,5 +1,4 @@
-pub mod machine;
-use machine::*;
+use intcode::machine::*;
 use std::collections::VecDeque;
 use std::io::Write;
 use std::thread;
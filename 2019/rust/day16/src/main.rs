@@ -2,97 +2,134 @@ use std::fs::File;
 use std::io::prelude::*;
 
 fn str_to_intvec(s: &str) -> Vec<isize> {
-    s.chars()
+    s.trim()
+        .chars()
         .map(|c| c.to_digit(10).unwrap() as isize)
         .collect()
 }
 
-fn get_first_n_w_offset(v: &Vec<isize>, n: usize, offset: usize) -> Vec<isize> {
-    v.into_iter().skip(offset).take(n).map(|&a| a).collect()
+fn get_first_n_w_offset(v: &[isize], n: usize, offset: usize) -> Vec<isize> {
+    v.iter().skip(offset).take(n).copied().collect()
 }
 
-fn phase(v: &Vec<isize>, iters: usize) -> Vec<isize> {
-    const BASE: [isize; 4] = [0, 1, 0, -1];
-    let mut acc = 0;
-    let mut outvec = Vec::new();
-    let mut invec = v.clone();
-
-    for iter in 0..iters {
-        println!("iter: {}", iter);
-        outvec = invec.clone();
-        for out_i in 0..invec.len() {
-            for in_i in 0..invec.len() {
-                acc += invec[in_i]
-                    * BASE[((in_i as f64 + 1f64) / (out_i as f64 + 1f64)) as usize % BASE.len()];
-            }
-            outvec[out_i] = acc.abs() % 10;
-            acc = 0;
+fn intvec_to_str(v: &[isize]) -> String {
+    v.iter().map(|d| d.to_string()).collect()
+}
+
+fn digits_to_number(v: &[isize]) -> usize {
+    v.iter().fold(0, |acc, d| acc * 10 + *d as usize)
+}
+
+// Output digit `out_i' uses the pattern 0, 1, 0, -1 with every element repeated out_i + 1 times,
+// shifted left by one. That is runs of out_i + 1 digits that are added, skipped, subtracted and
+// skipped, starting at out_i. With prefix sums every run costs O(1), and there are n / (out_i + 1)
+// runs, so a phase is O(n log n).
+fn phase(v: &[isize], iters: usize) -> Vec<isize> {
+    let n = v.len();
+    let mut invec = v.to_vec();
+    let mut prefix = vec![0; n + 1]; // prefix[i] is the sum of the first i digits
+    for _ in 0..iters {
+        for i in 0..n {
+            prefix[i + 1] = prefix[i] + invec[i];
+        }
+        let run_sum = |start: usize, len: usize| {
+            let end = (start + len).min(n);
+            prefix[end] - prefix[start.min(n)]
+        };
+        let outvec: Vec<isize> = (0..n)
+            .map(|out_i| {
+                let len = out_i + 1;
+                let mut acc = 0;
+                let mut start = out_i;
+                while start < n {
+                    acc += run_sum(start, len);
+                    acc -= run_sum(start + 2 * len, len);
+                    start += 4 * len;
+                }
+                acc.abs() % 10
+            })
+            .collect();
+        invec = outvec;
+    }
+    invec
+}
+
+// In the second half of the signal the pattern is all zeroes up to the output digit and all ones
+// after it, so every output digit is the sum of the digits from it to the end. Only the digits from
+// the offset onwards are needed, computed as reverse cumulative sums.
+fn decode_message(v: &[isize], repeats: usize, iters: usize) -> Vec<isize> {
+    let offset = digits_to_number(&get_first_n_w_offset(v, 7, 0));
+    let len = v.len() * repeats;
+    assert!(
+        offset >= len / 2 && offset + 8 <= len,
+        "Message offset {} is not in the second half of the signal",
+        offset
+    );
+    let mut tail: Vec<isize> = (offset..len).map(|i| v[i % v.len()]).collect();
+    for _ in 0..iters {
+        let mut acc = 0;
+        for digit in tail.iter_mut().rev() {
+            acc = (acc + *digit) % 10;
+            *digit = acc;
         }
-        invec = outvec.clone();
     }
-    outvec
+    get_first_n_w_offset(&tail, 8, 0)
 }
 
 fn main() {
-    let small_examples_in = ["12345678", "48226158", "34040438", "03415518"];
+    {
+        // Asserts
+        let small_examples_in = ["12345678", "48226158", "34040438", "03415518"];
 
-    let small_examples_out = ["48226158", "34040438", "03415518", "01029498"];
+        let small_examples_out = ["48226158", "34040438", "03415518", "01029498"];
 
-    let long_examples_in = [
-        "80871224585914546619083218645595",
-        "19617804207202209144916044189917",
-        "69317163492948606335995924319873",
-    ];
+        let long_examples_in = [
+            "80871224585914546619083218645595",
+            "19617804207202209144916044189917",
+            "69317163492948606335995924319873",
+        ];
 
-    let long_examples_out = ["24176176", "73745418", "52432133"];
+        let long_examples_out = ["24176176", "73745418", "52432133"];
 
-    for i in 0..small_examples_in.len() {
-        println!();
-        let res = phase(&str_to_intvec(small_examples_in[i]), 1);
-        let res = get_first_n_w_offset(&res, 8, 0);
-        let assert = str_to_intvec(small_examples_out[i]);
-        println!("small example num {}: {:?} == {:?}", i + 1, res, assert);
-        assert!(res == assert);
-    }
+        let message_examples_in = [
+            "03036732577212944063491565474664",
+            "02935109699940807407585447034323",
+            "03081770884921959731165446850517",
+        ];
 
-    for i in 0..long_examples_in.len() {
-        println!();
-        let res = phase(&str_to_intvec(long_examples_in[i]), 100);
-        let res = get_first_n_w_offset(&res, 8, 0);
-        let assert = str_to_intvec(long_examples_out[i]);
-        println!("long example num {}: {:?} == {:?}", i + 1, res, assert);
-        assert!(res == assert);
+        let message_examples_out = ["84462026", "78725270", "53553731"];
+
+        for i in 0..small_examples_in.len() {
+            let res = phase(&str_to_intvec(small_examples_in[i]), 1);
+            let res = get_first_n_w_offset(&res, 8, 0);
+            assert_eq!(res, str_to_intvec(small_examples_out[i]));
+        }
+
+        for i in 0..long_examples_in.len() {
+            let res = phase(&str_to_intvec(long_examples_in[i]), 100);
+            let res = get_first_n_w_offset(&res, 8, 0);
+            assert_eq!(res, str_to_intvec(long_examples_out[i]));
+        }
+
+        for i in 0..message_examples_in.len() {
+            let res = decode_message(&str_to_intvec(message_examples_in[i]), 10_000, 100);
+            assert_eq!(res, str_to_intvec(message_examples_out[i]));
+        }
     }
 
     let mut file = File::open("input.txt").expect("open file error");
     let mut contents = String::new();
     file.read_to_string(&mut contents).expect("read file error");
     let vec = str_to_intvec(&contents);
-    let res = phase(&vec, 100);
-    let res = get_first_n_w_offset(&res, 8, 0);
-
-    println!();
-    println!("part1: {:?}", res);
-
-    println!("part2:");
-    let offset = get_first_n_w_offset(&vec, 7, 0)
-        .iter()
-        .fold(0, |acc, i| acc * 10 + *i as usize);
-    println!("offset: {}", offset);
-    let mut vec2 = vec.clone();
-    for _ in 0..10_000 - 1 {
-        vec2.extend(&vec);
+    {
+        // Part 1
+        let res = phase(&vec, 100);
+        let res = get_first_n_w_offset(&res, 8, 0);
+        println!("Part 1: Ans is: {}", intvec_to_str(&res));
+    }
+    {
+        // Part 2
+        let res = decode_message(&vec, 10_000, 100);
+        println!("Part 2: Ans is: {}", intvec_to_str(&res));
     }
-
-    println!("vec.len(): {}", vec.len());
-    println!("vec2.len(): {}", vec2.len());
-    println!(
-        "vec2 @ {}: {:?}",
-        offset,
-        get_first_n_w_offset(&vec2, 8, offset)
-    );
-
-    let res = phase(&vec2, 100);
-    let res = get_first_n_w_offset(&res, 8, offset);
-    println!("part2: {:?}", res);
 }
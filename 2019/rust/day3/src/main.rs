@@ -1,58 +1,12 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::cmp;
+use std::collections::HashMap;
 use std::convert::TryInto;
-use std::iter::FromIterator;
 
 type Coord = (isize, isize);
-type Sect = (Coord, Coord);
 type Dirs = (char, isize);
 
-fn intersection(s1: Sect, s2: Sect) -> Option<Coord>
-{
-    /*
-    minx1 < (minx2 == maxx2) < maxx1 &&
-    miny2 < (miny1 == maxy1) < maxy2
-    - OR -
-    minx2 < (minx1 == maxx1) < maxx2 &&
-    miny1 < (miny2 == maxy2) < maxy1
-    */
-
-    let mut ret : Option<Coord> = None;
-
-    let minx1 = cmp::min((s1.0).0, (s1.1).0);
-    let maxx1 = cmp::max((s1.0).0, (s1.1).0);
-    let miny1 = cmp::min((s1.0).1, (s1.1).1);
-    let maxy1 = cmp::max((s1.0).1, (s1.1).1);
-    let minx2 = cmp::min((s2.0).0, (s2.1).0);
-    let maxx2 = cmp::max((s2.0).0, (s2.1).0);
-    let miny2 = cmp::min((s2.0).1, (s2.1).1);
-    let maxy2 = cmp::max((s2.0).1, (s2.1).1);
-
-    // First case
-    if
-    (minx2 == maxx2) &&
-    (minx1 < minx2) &&
-    (maxx2 < maxx1) &&
-    (miny1 == maxy1) &&
-    (miny2 < miny1) &&
-    (maxy1 < maxy2)
-    {
-        ret = Some((minx2, miny1));
-    }
-    // First case
-    else if
-    (minx1 == maxx1) &&
-    (minx2 < minx1) &&
-    (maxx1 < maxx2) &&
-    (miny2 == maxy2) &&
-    (miny1 < miny2) &&
-    (maxy2 < maxy1)
-    {
-        ret = Some((minx1, miny2));
-    }
-    ret
-}
+const SVG_COLORS: [&str; 6] = ["#d62728", "#1f77b4", "#2ca02c", "#9467bd", "#ff7f0e", "#8c564b"];
 
 fn manhattan_distance(p1: Coord, p2: Coord) -> usize
 {
@@ -64,16 +18,6 @@ fn manhattan_distance(p1: Coord, p2: Coord) -> usize
     tot.try_into().unwrap()
 }
 
-fn track_length(v: &[Coord]) -> usize
-{
-    let mut len = 0;
-    if v.len() > 0 {for i in 0..v.len()-1
-    {
-        len += manhattan_distance(v[i], v[i+1]);
-    }}
-    len
-}
-
 // From https://stackoverflow.com/questions/30811107/getting-a-single-character-out-of-a-string#comment83958722_48482196
 // Thanks https://stackoverflow.com/users/155423/shepmaster !
 fn car_cdr(s: &str) -> (&str, &str) {
@@ -83,121 +27,247 @@ fn car_cdr(s: &str) -> (&str, &str) {
     }
 }
 
-fn calc(filestr: &str, prints: bool) -> (usize, usize)
+fn step(dir: char) -> Coord
 {
-    // Part 1
-    // let f = File::open("ex1.txt").expect("Unable to open file");
-    let f = File::open(filestr).expect("Unable to open file");
-    let f = BufReader::new(f);
-    let mut wires : Vec<Vec<Coord>> = Vec::new();
-    let mut directs : Vec<Vec<Dirs>> = Vec::new();
-
-    // Scan the lines of the file and create coordinates directly
-    for line in f.lines()
+    match dir
     {
-        let mut wire : Vec<Coord> = Vec::new();
-        let mut direction : Vec<Dirs> = Vec::new();
-        let mut last_coord : Coord = (0,0); // Start at 0
-        wire.push(last_coord); // Push origo
-        let line = line.expect("Unable to read line");
-        let splits : Vec<&str> = line.split(",").collect();
-        for split in splits
+        'R' => (1, 0),
+        'L' => (-1, 0),
+        'U' => (0, 1),
+        _ => (0, -1), // 'D', checked when parsing
+    }
+}
+
+// A wire starting at the origin, one (direction, length) pair per segment
+#[derive(Debug, Clone, PartialEq)]
+struct Wire
+{
+    segments: Vec<Dirs>,
+}
+
+impl Wire
+{
+    // Parses `R8,U5,L5,D3'
+    fn parse(line: &str) -> Result<Wire, String>
+    {
+        let mut segments = Vec::new();
+        for split in line.trim().split(',').filter(|s| !s.is_empty())
         {
-            let (dir, len) = car_cdr(split);
-            let dir = dir.chars().nth(0).unwrap();
-            let len = len.parse::<isize>().expect("Unable to parse segment");
-            direction.push((dir, len));
-            match dir
+            let (dir, len) = car_cdr(split.trim());
+            let dir = dir.chars().next().unwrap_or(' ');
+            if !"RLUD".contains(dir)
+            {
+                return Err(format!("Faulty direction in `{}'", split));
+            }
+            let len = len.parse::<isize>().map_err(|_| format!("Faulty length in `{}'", split))?;
+            if len < 0
             {
-                'R' => { last_coord = (last_coord.0+len,last_coord.1) },
-                'L' => { last_coord = (last_coord.0-len,last_coord.1) },
-                'U' => { last_coord = (last_coord.0,last_coord.1+len) },
-                'D' => { last_coord = (last_coord.0,last_coord.1-len) },
-                c   => { println!("Faulty character: {}", c) },
+                return Err(format!("Negative length in `{}'", split));
             }
-            wire.push(last_coord);
+            segments.push((dir, len));
         }
-        wires.push(wire);
-        directs.push(direction);
+        Ok(Wire { segments })
     }
 
-    let mut num_checks : usize = 0;
-    let mut num_isects : usize = 0;
-    let mut closest_distance : usize = std::usize::MAX;
-    let mut closest_distance_by_track_length : usize = std::usize::MAX;
+    fn from_file(filestr: &str) -> Result<Vec<Wire>, String>
+    {
+        let f = File::open(filestr).map_err(|e| format!("Unable to open {}: {}", filestr, e))?;
+        let mut wires = Vec::new();
+        for line in BufReader::new(f).lines()
+        {
+            let line = line.map_err(|e| format!("Unable to read {}: {}", filestr, e))?;
+            if !line.trim().is_empty()
+            {
+                wires.push(Wire::parse(&line)?);
+            }
+        }
+        Ok(wires)
+    }
 
-    if prints {for dir in directs
+    // The origin and the end of every segment
+    fn corners(&self) -> Vec<Coord>
     {
-        println!("{:?}, {}", dir, dir.len());
-    }}
+        let mut corners = vec![(0, 0)];
+        let mut last_coord: Coord = (0, 0);
+        for &(dir, len) in &self.segments
+        {
+            let (dx, dy) = step(dir);
+            last_coord = (last_coord.0 + dx * len, last_coord.1 + dy * len);
+            corners.push(last_coord);
+        }
+        corners
+    }
 
-    if prints {for wire in &wires
+    // Every point the wire passes after leaving the origin, with the steps taken to get there
+    fn points(&self) -> impl Iterator<Item = (Coord, usize)> + '_
     {
-        println!("{:?}, {}", wire, wire.len());
-    }}
+        self.segments
+            .iter()
+            .flat_map(|&(dir, len)| std::iter::repeat_n(step(dir), len as usize))
+            .scan((0, 0), |pos: &mut Coord, (dx, dy)| {
+                *pos = (pos.0 + dx, pos.1 + dy);
+                Some(*pos)
+            })
+            .zip(1..)
+    }
+}
 
-    // Check for intersections
-    for w1 in 0..wires.len()
+// A point where at least two wires meet
+#[derive(Debug, Clone, PartialEq)]
+struct Crossing
+{
+    pos: Coord,
+    steps: Vec<Option<usize>>, // Steps each wire takes to first reach the point, None if it never does
+}
+
+impl Crossing
+{
+    fn distance(&self) -> usize
+    {
+        manhattan_distance((0, 0), self.pos)
+    }
+
+    fn total_steps(&self) -> usize
+    {
+        self.steps.iter().flatten().sum()
+    }
+}
+
+// Every point visited by more than one wire, a wire crossing itself does not count. Each point is
+// hashed once per wire, so this is linear in the total wire length.
+fn crossings(wires: &[Wire]) -> Vec<Crossing>
+{
+    let mut first_visit: HashMap<Coord, (usize, usize)> = HashMap::new(); // Point -> first wire there and its steps
+    let mut found: HashMap<Coord, Vec<Option<usize>>> = HashMap::new();
+    for (w, wire) in wires.iter().enumerate()
     {
-        for w2 in w1+1..wires.len()
+        for (pos, steps) in wire.points()
         {
-            for c1 in 0..wires[w1].len()-1
+            let &mut (first_wire, first_steps) = first_visit.entry(pos).or_insert((w, steps));
+            if first_wire == w
             {
-                for c2 in 0..wires[w2].len()-1
-                {
-                    if prints {println!("Comparing ({:?}->{:?}) with ({:?}->{:?})", wires[w1][c1], wires[w1][c1+1], wires[w2][c2], wires[w2][c2+1])}
-                    num_checks += 1;
-                    match intersection((wires[w1][c1], wires[w1][c1+1]), (wires[w2][c2], wires[w2][c2+1]))
-                    {
-                        None => {},
-                        Some(c) => {
-                            num_isects += 1;
-                            let cd = manhattan_distance((0,0), c);
-                            closest_distance = cmp::min(closest_distance, cd);
-
-                            let mut track1 = Vec::from_iter(wires[w1][0..c1+1].iter().cloned());
-                            let mut track2 = Vec::from_iter(wires[w2][0..c2+1].iter().cloned());
-                            track1.push(c);
-                            track2.push(c);
-                            let tl = track_length(&track1)+track_length(&track2);
-                            closest_distance_by_track_length = cmp::min(closest_distance_by_track_length, tl);
-
-                            if prints {println!("\tIntersection found at {:?}: {:?}", c, (cd, tl))}
-                            if prints {println!("\tOrig1:  {:?}, {}", wires[0], wires[0].len());}
-                            if prints {println!("\tOrig2:  {:?}, {}", wires[1], wires[1].len());}
-                            if prints {println!("\tTrack1: {:?}, {}", track1, track1.len())}
-                            if prints {println!("\tTrack2: {:?}, {}", track2, track2.len())}
-                        },
-                    }
-                }
+                continue;
             }
+            let crossing = found.entry(pos).or_insert_with(|| {
+                let mut v = vec![None; wires.len()];
+                v[first_wire] = Some(first_steps);
+                v
+            });
+            crossing[w].get_or_insert(steps);
         }
     }
+    let mut crossings: Vec<Crossing> = found
+        .into_iter()
+        .map(|(pos, steps)| Crossing { pos, steps })
+        .collect();
+    crossings.sort_by_key(|c| (c.distance(), c.pos));
+    crossings
+}
+
+// Closest crossing to the origin, and the fewest combined steps to a crossing
+fn closest(crossings: &[Crossing]) -> Option<(usize, usize)>
+{
+    let distance = crossings.iter().map(Crossing::distance).min()?;
+    let steps = crossings.iter().map(Crossing::total_steps).min()?;
+    Some((distance, steps))
+}
+
+// The wires as an SVG image, up is up, crossings are circled and the origin is a black dot
+fn to_svg(wires: &[Wire], crossings: &[Crossing]) -> String
+{
+    let all: Vec<Coord> = wires.iter().flat_map(|w| w.corners()).collect();
+    let minx = all.iter().map(|c| c.0).min().unwrap_or(0);
+    let maxx = all.iter().map(|c| c.0).max().unwrap_or(0);
+    let miny = all.iter().map(|c| -c.1).min().unwrap_or(0);
+    let maxy = all.iter().map(|c| -c.1).max().unwrap_or(0);
+    let size = (maxx - minx).max(maxy - miny).max(1);
+    let stroke = size as f64 / 500.0;
+    let margin = size / 50 + 1;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        minx - margin,
+        miny - margin,
+        maxx - minx + 2 * margin,
+        maxy - miny + 2 * margin
+    );
+    for (w, wire) in wires.iter().enumerate()
+    {
+        let points: Vec<String> = wire.corners().iter().map(|c| format!("{},{}", c.0, -c.1)).collect();
+        out += &format!(
+            "  <polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+            points.join(" "),
+            SVG_COLORS[w % SVG_COLORS.len()],
+            stroke
+        );
+    }
+    for crossing in crossings
+    {
+        out += &format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+            crossing.pos.0,
+            -crossing.pos.1,
+            stroke * 4.0,
+            stroke
+        );
+    }
+    out += &format!("  <circle cx=\"0\" cy=\"0\" r=\"{}\" fill=\"black\"/>\n", stroke * 4.0);
+    out += "</svg>\n";
+    out
+}
 
-    println!("Read {} complete ({} checks, {} isects), got: {:?}", filestr, num_checks, num_isects, (closest_distance, closest_distance_by_track_length));
-    (closest_distance, closest_distance_by_track_length)
+fn calc(filestr: &str) -> (usize, usize)
+{
+    let wires = Wire::from_file(filestr).unwrap();
+    closest(&crossings(&wires)).expect("The wires never cross")
 }
 
+// Usage: day3 [--svg <file>]
 fn main()
 {
     // Unit tests
-    assert!(intersection(((1,0),(1,2)), ((3,3),(4,4))) == None);
-    assert!(intersection(((1,0),(1,2)), ((0,1),(2,1))) == Some((1,1)));
-    assert!(intersection(((1,0),(1,2)), ((0,1),(-2,1))) == None);
-
     assert!(manhattan_distance((0,0), (1,1)) == 2);
     assert!(manhattan_distance((0,0), (2,2)) == 4);
     assert!(manhattan_distance((-2,-2), (2,2)) == 8);
     assert!(manhattan_distance((2,-2), (2,2)) == 4);
     assert!(manhattan_distance((-2,2), (2,2)) == 4);
     assert!(manhattan_distance((3,3), (3,3)) == 0);
-    
-    assert!(track_length(&[(2,0),(1,0),(1,2)]) == 3);
-    assert!(track_length(&[(0,0)]) == 0);
-    assert!(track_length(&[]) == 0);
-    assert!(track_length(&[(0,0),(-10,0),(-13,0),(-13,20),(0,20),(0,0)]) == 66);
-
-    assert!(calc("ex1.txt", false) == (159, 610));
-    assert!(calc("ex2.txt", false) == (135, 410));
-    calc("input.txt", false);
+
+    assert!(Wire::parse("R8,U5,L5,D3").unwrap().corners() == vec![(0,0),(8,0),(8,5),(3,5),(3,2)]);
+    assert!(Wire::parse("R8,X5").is_err());
+    assert!(Wire::parse("R8,U").is_err());
+
+    let wires = vec![Wire::parse("R8,U5,L5,D3").unwrap(), Wire::parse("U7,R6,D4,L4").unwrap()];
+    let found = crossings(&wires);
+    assert!(found.iter().map(|c| c.pos).collect::<Vec<_>>() == vec![(3,3),(6,5)]);
+    assert!(found[0].steps == vec![Some(20), Some(20)]);
+    assert!(found[1].steps == vec![Some(15), Some(15)]);
+    assert!(closest(&found) == Some((6, 30)));
+
+    // Three wires, the third only meets the first
+    let wires = vec![
+        Wire::parse("R8,U5,L5,D3").unwrap(),
+        Wire::parse("U7,R6,D4,L4").unwrap(),
+        Wire::parse("D1,R8,U3").unwrap(),
+    ];
+    let found = crossings(&wires);
+    assert!(found.len() == 5);
+    assert!(found[1].pos == (8,0) && found[1].steps == vec![Some(8), None, Some(10)]);
+    assert!(found[0].steps == vec![Some(20), Some(20), None]);
+
+    assert!(calc("ex1.txt") == (159, 610));
+    assert!(calc("ex2.txt") == (135, 410));
+
+    let wires = Wire::from_file("input.txt").unwrap();
+    let found = crossings(&wires);
+    let (distance, steps) = closest(&found).expect("The wires never cross");
+    println!("Part 1: Ans is: {}", distance);
+    println!("Part 2: Ans is: {}", steps);
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(idx) = args.iter().position(|arg| arg == "--svg")
+    {
+        let file = args.get(idx + 1).expect("Usage: day3 [--svg <file>]");
+        std::fs::write(file, to_svg(&wires, &found)).expect("Unable to write svg");
+    }
 }
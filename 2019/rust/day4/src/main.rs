@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+const MAX_DIGITS: usize = 18; // Everything below 10^18 fits the counters
+
 fn get_input_range() -> (usize, usize)
 {
     let mut lower : usize = 100000;
@@ -20,141 +23,269 @@ fn get_input_range() -> (usize, usize)
     (lower, upper)
 }
 
-// The criterias to meet are:
-// It is a six-digit number.
-// The value is within the range given in your puzzle input.
-// Two adjacent digits are the same (like 22 in 122345).
-// Going from left to right, the digits never decrease; they only ever increase or stay the same (like 111123 or 135679).
-fn meets_criteria(a: usize, lower: usize, upper: usize) -> bool
+// Every password is in the range and its digits never decrease going from left to right, the
+// rules add conditions on the runs of equal digits
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Rule
+{
+    AdjacentPair,   // Two adjacent digits are the same (like 22 in 122345)
+    ExactPair,      // Some run of equal digits is exactly two long (112233, but not 123444)
+    MaxRun(usize),  // No run of equal digits is longer than this
+}
+
+// What the rules need to know about the digits so far. Since the digits never decrease, equal
+// digits are always next to each other.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+struct Runs
+{
+    last: Option<usize>, // The last digit
+    run: usize,          // Length of the run the last digit is part of
+    longest: usize,      // Longest finished run
+    has_pair: bool,      // Some finished run is at least two long
+    has_exact_pair: bool,
+}
+
+impl Runs
 {
-    let mut res = true;
-    // let (lower, upper) = get_input_range();
-    let digits : Vec<_> = a.to_string()
-        .chars()
-        .map(|d| d.to_digit(10).unwrap())
-        .collect();
-
-    // Check bounds
-    // We assume here that the lower bound is an 6 digit number, so
-    // it automatically accounts also for that criteria
-    if a > upper || a < lower { res = false }
-
-    // Check if at least two digits next to each other are the same
-    let mut identical_adjacents = false;
-    for i in 0..digits.len()-1
-    {
-        if digits[i] == digits[i+1]
+    fn new() -> Runs
+    {
+        Runs { last: None, run: 0, longest: 0, has_pair: false, has_exact_pair: false }
+    }
+
+    fn finish(self) -> Runs
+    {
+        Runs {
+            last: self.last,
+            run: 0,
+            longest: self.longest.max(self.run),
+            has_pair: self.has_pair || self.run >= 2,
+            has_exact_pair: self.has_exact_pair || self.run == 2,
+        }
+    }
+
+    fn push(self, digit: usize) -> Runs
+    {
+        if self.last == Some(digit)
         {
-            identical_adjacents = true;
+            return Runs { run: self.run + 1, ..self };
         }
-        if digits[i+1] < digits[i] { res = false }
+        Runs { last: Some(digit), run: 1, ..self.finish() }
+    }
+
+    // Whether a run that can still grow has already broken a rule
+    fn broken(&self, rules: &[Rule]) -> bool
+    {
+        rules.iter().any(|rule| match rule {
+            Rule::MaxRun(max) => self.run.max(self.longest) > *max,
+            _ => false,
+        })
+    }
+
+    fn passes(&self, rules: &[Rule]) -> bool
+    {
+        let runs = self.finish();
+        rules.iter().all(|rule| match rule {
+            Rule::AdjacentPair => runs.has_pair,
+            Rule::ExactPair => runs.has_exact_pair,
+            Rule::MaxRun(max) => runs.longest <= *max,
+        })
+    }
+}
+
+fn to_digits(a: usize) -> Vec<usize>
+{
+    a.to_string().chars().map(|d| d.to_digit(10).unwrap() as usize).collect()
+}
+
+// Checks a single number, the same rules as `count' and `list'
+fn meets_rules(a: usize, lower: usize, upper: usize, rules: &[Rule]) -> bool
+{
+    let digits = to_digits(a);
+    if a < lower || a > upper || digits.windows(2).any(|w| w[1] < w[0])
+    {
+        return false;
     }
-    if !identical_adjacents { res = false }
+    digits.iter().fold(Runs::new(), |runs, d| runs.push(*d)).passes(rules)
+}
 
-    res
+struct Counter<'a>
+{
+    rules: &'a [Rule],
+    memo: HashMap<(usize, Runs), usize>, // (digits left, runs so far) -> passing completions
 }
 
-// Additionally, for part 2 the doubly digits cannot be part
-// of a triplet or larger
-fn meets_criteria_part2(a: usize, lower: usize, upper: usize) -> bool
+impl Counter<'_>
 {
-    let mut res = true;
-    // let (lower, upper) = get_input_range();
-    let digits : Vec<_> = a.to_string()
-        .chars()
-        .map(|d| d.to_digit(10).unwrap())
-        .collect();
-
-    // Check bounds
-    // We assume here that the lower bound is an 6 digit number, so
-    // it automatically accounts also for that criteria
-    if a > upper || a < lower { res = false }
-
-    // Check if at least two digits next to each other are the same
-    let mut identical_adjacents = false;
-    for i in 0..digits.len()-1
-    {
-        if i == 0
+    // Number of ways to finish a password with `left' more digits, not lower than `min'
+    fn free(&mut self, left: usize, runs: Runs, min: usize) -> usize
+    {
+        if runs.broken(self.rules)
         {
-            if  (digits[i] == digits[i+1]) &&
-                (digits[i] != digits[i+2])
-            {
-                // println!("{} adjacent at {}", a, i);
-                identical_adjacents = true;
-            }
+            return 0;
+        }
+        if left == 0
+        {
+            return runs.passes(self.rules) as usize;
+        }
+        if let Some(count) = self.memo.get(&(left, runs))
+        {
+            return *count;
         }
-        else if i < digits.len()-2
+        let count = (runs.last.unwrap_or(min)..10).map(|d| self.free(left - 1, runs.push(d), min)).sum();
+        if runs.last.is_some()
         {
-            if  (digits[i] == digits[i+1]) &&
-                (digits[i] != digits[i+2]) &&
-                (digits[i] != digits[i-1])
+            self.memo.insert((left, runs), count);
+        }
+        count
+    }
+
+    // Passwords from 0 to `upper'
+    fn up_to(&mut self, upper: usize) -> usize
+    {
+        let digits = to_digits(upper);
+        // Shorter numbers, leading zeroes are not allowed so only 0 itself starts with one
+        let mut count: usize = (1..digits.len()).map(|len| self.free(len, Runs::new(), if len == 1 { 0 } else { 1 })).sum();
+        // Numbers as long as `upper', below it from the first digit where they differ
+        let mut runs = Runs::new();
+        let mut min = if digits.len() == 1 { 0 } else { 1 };
+        for (i, &digit) in digits.iter().enumerate()
+        {
+            for d in min..digit
+            {
+                count += self.free(digits.len() - i - 1, runs.push(d), d);
+            }
+            if digit < min
             {
-                // println!("{} adjacent at {}", a, i);
-                identical_adjacents = true;
+                return count; // Every non-decreasing number of this length from here on is larger
             }
+            runs = runs.push(digit);
+            min = digit;
+        }
+        count + (!runs.broken(self.rules) && runs.passes(self.rules)) as usize
+    }
+}
+
+// Number of passwords in lower..=upper
+fn count(lower: usize, upper: usize, rules: &[Rule]) -> usize
+{
+    assert!(upper < 10usize.pow(MAX_DIGITS as u32), "At most {} digits", MAX_DIGITS);
+    if upper < lower
+    {
+        return 0;
+    }
+    let mut counter = Counter { rules, memo: HashMap::new() };
+    let below = if lower == 0 { 0 } else { counter.up_to(lower - 1) };
+    counter.up_to(upper) - below
+}
+
+// All passwords in lower..=upper in increasing order, by only building non-decreasing numbers
+fn list(lower: usize, upper: usize, rules: &[Rule]) -> Vec<usize>
+{
+    fn walk(prefix: usize, left: usize, runs: Runs, lower: usize, upper: usize, rules: &[Rule], out: &mut Vec<usize>)
+    {
+        if runs.broken(rules)
+        {
+            return;
         }
-        else
+        let last = runs.last.unwrap_or(0);
+        let scale = 10usize.pow(left as u32);
+        // Repeating the last digit gives the smallest completion, nines the largest
+        let smallest = prefix * scale + last * (scale - 1) / 9;
+        let largest = prefix * scale + (scale - 1);
+        if largest < lower || smallest > upper
         {
-            if  (digits[i] == digits[i+1]) &&
-                (digits[i] != digits[i-1])
+            return;
+        }
+        if left == 0
+        {
+            if runs.passes(rules)
             {
-                // println!("{} adjacent at {}", a, i);
-                identical_adjacents = true;
+                out.push(prefix);
             }
+            return;
+        }
+        for d in last..10
+        {
+            walk(prefix * 10 + d, left - 1, runs.push(d), lower, upper, rules, out);
         }
     }
-    if !identical_adjacents { res = false }
 
-    for i in 0..digits.len()-1
+    assert!(upper < 10usize.pow(MAX_DIGITS as u32), "At most {} digits", MAX_DIGITS);
+    let mut out = Vec::new();
+    if upper < lower
     {
-        if digits[i+1] < digits[i] { res = false }
+        return out;
     }
-
-    res
+    if lower == 0 && Runs::new().push(0).passes(rules)
+    {
+        out.push(0);
+    }
+    for len in to_digits(lower).len()..=to_digits(upper).len()
+    {
+        for first in 1..10
+        {
+            walk(first, len - 1, Runs::new().push(first), lower, upper, rules, &mut out);
+        }
+    }
+    out
 }
 
+// Usage: day4 [--list]
 fn main()
 {
     let (lower, upper) = get_input_range();
+    let part1 = [Rule::AdjacentPair];
+    let part2 = [Rule::ExactPair];
 
     // We use debug assert here so that we can do cargo run --release to run without the assertions
-    // debug_assert!(!meets_criteria(111111));
-    debug_assert!(!meets_criteria(223450, lower, upper));
-    debug_assert!(!meets_criteria(123789, lower, upper));
-    debug_assert!(!meets_criteria(123, lower, upper));
-    debug_assert!(meets_criteria(134566, lower, upper));
-
-    debug_assert!(meets_criteria_part2(223344, lower, upper));
-    debug_assert!(!meets_criteria_part2(234555, lower, upper));
-    debug_assert!(meets_criteria_part2(222233, lower, upper));
-    debug_assert!(!meets_criteria_part2(222234, lower, upper));
-    debug_assert!(!meets_criteria_part2(234567, lower, upper));
-    debug_assert!(!meets_criteria_part2(234555, lower, upper));
-    debug_assert!(!meets_criteria_part2(222222, lower, upper));
-    debug_assert!(meets_criteria_part2(234566, lower, upper));
+    debug_assert!(!meets_rules(223450, lower, upper, &part1));
+    debug_assert!(!meets_rules(123789, lower, upper, &part1));
+    debug_assert!(!meets_rules(123, lower, upper, &part1));
+    debug_assert!(meets_rules(134566, lower, upper, &part1));
+
+    debug_assert!(meets_rules(223344, lower, upper, &part2));
+    debug_assert!(!meets_rules(234555, lower, upper, &part2));
+    debug_assert!(meets_rules(222233, lower, upper, &part2));
+    debug_assert!(!meets_rules(222234, lower, upper, &part2));
+    debug_assert!(!meets_rules(234567, lower, upper, &part2));
+    debug_assert!(!meets_rules(222222, lower, upper, &part2));
+    debug_assert!(meets_rules(234566, lower, upper, &part2));
+
+    debug_assert!(meets_rules(112233, 0, 999999, &[Rule::AdjacentPair, Rule::MaxRun(2)]));
+    debug_assert!(!meets_rules(111223, 0, 999999, &[Rule::MaxRun(2)]));
+
+    // Counting and listing against checking every number
+    for rules in [&part1[..], &part2[..], &[Rule::MaxRun(3)], &[Rule::AdjacentPair, Rule::MaxRun(4)], &[]].iter()
+    {
+        for &(lo, hi) in [(lower, upper), (0, 0), (0, 1234), (5, 5), (99, 100), (11, 11), (100000, 999999)].iter()
+        {
+            let brute: Vec<usize> = (lo..=hi).filter(|a| meets_rules(*a, lo, hi, rules)).collect();
+            debug_assert!(list(lo, hi, rules) == brute);
+            debug_assert!(count(lo, hi, rules) == brute.len());
+        }
+    }
+    debug_assert!(count(1, 10usize.pow(12) - 1, &[]) == list(1, 10usize.pow(12) - 1, &[]).len());
+    debug_assert!(count(123456789, 98765432109, &part2) == list(123456789, 98765432109, &part2).len());
+    // Non-decreasing 18 digit numbers pick 18 of the digits 1 to 9 with repetition, C(26, 8)
+    debug_assert!(count(10usize.pow(17), 10usize.pow(18) - 1, &[]) == 1562275);
     println!("Asserts passed");
 
+    let print_list = std::env::args().any(|arg| arg == "--list");
     {
-        let mut num_pass = 0;
-        for number in lower..upper
+        // Part 1
+        if print_list
         {
-            if meets_criteria(number, lower, upper)
-            {
-                num_pass = num_pass+1;
-            }
+            println!("{:?}", list(lower, upper, &part1));
         }
-        println!("Num passes part1: {}", num_pass);
+        println!("Num passes part1: {}", count(lower, upper, &part1));
     }
     {
-        let mut num_pass = 0;
-        for number in lower..upper
+        // Part 2
+        if print_list
         {
-            if meets_criteria_part2(number, lower, upper)
-            {
-                num_pass = num_pass+1;
-            }
+            println!("{:?}", list(lower, upper, &part2));
         }
         // Answer is not 1837
-        println!("Num passes part2: {}", num_pass);
+        println!("Num passes part2: {}", count(lower, upper, &part2));
     }
 }
@@ -1,78 +1,263 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::collections::HashMap;
-use std::iter::Iterator;
-use std::cmp;
-
-fn get_distance_to_com(
-    orb: &String,
-    map: &HashMap<String, String>,
-) -> usize {
-    let mut counter : usize = 1;
-    if *orb == "COM".to_string() { return counter }
-    let mut orb = map.get(orb).unwrap();
-    loop
-    {
-        counter += 1;
-        if *orb == "COM".to_string() { return counter }
-        orb = map.get(orb).unwrap();
-    }
+
+// Bodies and what they orbit, checked to form a single tree when built. Depths and ancestor tables
+// are computed once, so depth queries are O(1) and common ancestors O(log depth).
+struct OrbitTree {
+    names: Vec<String>,
+    index: HashMap<String, usize>, // Name -> position in `names'
+    parent: Vec<Option<usize>>,    // The body orbited, None for the root
+    depth: Vec<usize>,             // Number of direct and indirect orbits, 0 for the root
+    ancestors: Vec<Vec<usize>>,    // ancestors[k][v] is 2^k steps up from v, the root above the root
+    root: usize,
 }
 
-fn find_santa(
-    map: &HashMap<String, String>,
-) -> usize {
-    // First find out santa's path to COM
-    let mut out = 0;
-    let mut santa_path : Vec<String> = Vec::new();
-    let mut orb = map.get("SAN").unwrap();
-    loop {
-        santa_path.push(orb.to_string());
-        if *orb == "COM".to_string() { break; }
-        orb = map.get(orb).unwrap();
-    }
-
-    // Then find the first orbital from the path of
-    // YOU to COM that coincides with santa's
-    let mut orb = map.get("YOU").unwrap();
-    loop {
-        if santa_path.contains(orb) {
-            out += santa_path.iter().position(|r| r == orb).unwrap();
-            break;
+impl OrbitTree {
+    // One `A)B' per line, B orbits A
+    fn parse(lines: &[String]) -> Result<OrbitTree, String> {
+        let mut names: Vec<String> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut parent: Vec<Option<usize>> = Vec::new();
+        let mut id = |name: &str| {
+            *index.entry(name.to_string()).or_insert_with(|| {
+                names.push(name.to_string());
+                parent.push(None);
+                names.len() - 1
+            })
+        };
+        let mut orbits = Vec::new();
+        for line in lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let splits: Vec<&str> = line.split(')').collect();
+            if splits.len() != 2 || splits[0].is_empty() || splits[1].is_empty() {
+                return Err(format!("Expected `A)B', got `{}'", line));
+            }
+            orbits.push((id(splits[0]), id(splits[1])));
+        }
+        for (orb, sat) in orbits {
+            if let Some(other) = parent[sat] {
+                return Err(format!(
+                    "{} orbits both {} and {}",
+                    names[sat], names[other], names[orb]
+                ));
+            }
+            parent[sat] = Some(orb);
+        }
+
+        let roots: Vec<usize> = (0..names.len()).filter(|v| parent[*v].is_none()).collect();
+        let root = match roots.len() {
+            1 => roots[0],
+            0 if names.is_empty() => return Err("No orbits".to_string()),
+            0 => return Err("Every body orbits another, there is no root".to_string()),
+            _ => {
+                let mut roots: Vec<&str> = roots.iter().map(|v| names[*v].as_str()).collect();
+                roots.sort_unstable();
+                return Err(format!("More than one root: {}", roots.join(", ")));
+            }
+        };
+
+        // Memoised depths, walking up until a body with a known depth
+        let mut depth: Vec<Option<usize>> = vec![None; names.len()];
+        depth[root] = Some(0);
+        let mut on_path = vec![false; names.len()];
+        for start in 0..names.len() {
+            let mut path = Vec::new();
+            let mut v = start;
+            while depth[v].is_none() {
+                if on_path[v] {
+                    let cycle_start = path.iter().position(|u| *u == v).unwrap();
+                    let cycle: Vec<&str> = path[cycle_start..]
+                        .iter()
+                        .map(|u: &usize| names[*u].as_str())
+                        .collect();
+                    return Err(format!("Orbits form a cycle: {}", cycle.join(")")));
+                }
+                on_path[v] = true;
+                path.push(v);
+                v = parent[v].unwrap(); // Only the root has no parent, and its depth is known
+            }
+            let mut d = depth[v].unwrap();
+            for u in path.into_iter().rev() {
+                d += 1;
+                depth[u] = Some(d);
+                on_path[u] = false;
+            }
+        }
+        let depth: Vec<usize> = depth.into_iter().map(|d| d.unwrap()).collect();
+
+        let max_depth = depth.iter().copied().max().unwrap_or(0);
+        let mut ancestors = vec![(0..names.len())
+            .map(|v| parent[v].unwrap_or(root))
+            .collect::<Vec<usize>>()];
+        while 1 << ancestors.len() <= max_depth {
+            let last = ancestors.last().unwrap();
+            let next = last.iter().map(|v| last[*v]).collect();
+            ancestors.push(next);
+        }
+
+        Ok(OrbitTree {
+            names,
+            index,
+            parent,
+            depth,
+            ancestors,
+            root,
+        })
+    }
+
+    fn from_file(file: &str) -> Result<OrbitTree, String> {
+        let f = File::open(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        let lines: Result<Vec<String>, _> = BufReader::new(f).lines().collect();
+        OrbitTree::parse(&lines.map_err(|e| format!("Unable to read {}: {}", file, e))?)
+    }
+
+    fn id(&self, name: &str) -> Result<usize, String> {
+        self.index
+            .get(name)
+            .copied()
+            .ok_or(format!("Unknown body {}", name))
+    }
+
+    fn root(&self) -> &str {
+        &self.names[self.root]
+    }
+
+    fn parent(&self, name: &str) -> Result<Option<&str>, String> {
+        Ok(self.parent[self.id(name)?].map(|v| self.names[v].as_str()))
+    }
+
+    fn depth(&self, name: &str) -> Result<usize, String> {
+        Ok(self.depth[self.id(name)?])
+    }
+
+    fn max_depth(&self) -> usize {
+        self.depth.iter().copied().max().unwrap_or(0)
+    }
+
+    // Direct and indirect orbits of every body
+    fn total_orbits(&self) -> usize {
+        self.depth.iter().sum()
+    }
+
+    fn up(&self, mut v: usize, steps: usize) -> usize {
+        for (k, table) in self.ancestors.iter().enumerate() {
+            if steps & (1 << k) != 0 {
+                v = table[v];
+            }
         }
-        orb = map.get(orb).unwrap();
-        out += 1; // Use the out var to store our iteration loop
+        v
+    }
+
+    // Lowest common ancestor by binary lifting
+    fn lca_id(&self, a: usize, b: usize) -> usize {
+        let (mut a, mut b) = if self.depth[a] >= self.depth[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        a = self.up(a, self.depth[a] - self.depth[b]);
+        if a == b {
+            return a;
+        }
+        for table in self.ancestors.iter().rev() {
+            if table[a] != table[b] {
+                a = table[a];
+                b = table[b];
+            }
+        }
+        self.ancestors[0][a]
+    }
+
+    // The deepest body both `a' and `b' orbit directly or indirectly, or are
+    fn lca(&self, a: &str, b: &str) -> Result<&str, String> {
+        Ok(&self.names[self.lca_id(self.id(a)?, self.id(b)?)])
+    }
+
+    // Bodies from `a' to `b', both included, going through their common ancestor
+    fn path(&self, a: &str, b: &str) -> Result<Vec<&str>, String> {
+        let (a, b) = (self.id(a)?, self.id(b)?);
+        let lca = self.lca_id(a, b);
+        let climb = |mut v: usize| {
+            let mut out = Vec::new();
+            while v != lca {
+                out.push(v);
+                v = self.parent[v].unwrap();
+            }
+            out
+        };
+        let mut path = climb(a);
+        path.push(lca);
+        path.extend(climb(b).into_iter().rev());
+        Ok(path.into_iter().map(|v| self.names[v].as_str()).collect())
     }
-    out
+
+    // Orbital transfers to get from what `a' orbits to what `b' orbits
+    fn transfers(&self, a: &str, b: &str) -> Result<usize, String> {
+        let from = self.parent(a)?.ok_or(format!("{} orbits nothing", a))?;
+        let to = self.parent(b)?.ok_or(format!("{} orbits nothing", b))?;
+        Ok(self.path(from, to)?.len() - 1)
+    }
+
+    // Graphviz format, edges point from a body to what orbits it
+    fn to_dot(&self) -> String {
+        let mut out = "digraph orbits {\n".to_string();
+        for (v, parent) in self.parent.iter().enumerate() {
+            if let Some(parent) = parent {
+                out += &format!("    \"{}\" -> \"{}\";\n", self.names[*parent], self.names[v]);
+            }
+        }
+        out += "}\n";
+        out
+    }
+}
+
+fn lines(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|l| l.to_string()).collect()
 }
 
+// Usage: day6 [--dot]
 fn main() {
-    let mut map = HashMap::new();
-    let f = File::open("input.txt").expect("Unable to open file");
-    let f = BufReader::new(f);
-
-    let mut counter = 0;
-    for line in f.lines() {
-        let line = line.expect("Unable to read line");
-        let mut splits : Vec<&str> = line.split(")").collect();
-        let sat = splits.pop().unwrap().to_string();
-        let orb = splits.pop().unwrap().to_string();
-        map.insert(sat, orb);
-        counter += 1;
-    }
-    println!("Read {} lines", counter);
-
-    let mut tot: usize = 0;
-    let mut max: usize = 0;
-    for (_sat, dat) in &map {
-        let dist = get_distance_to_com(&dat, &map);
-        max = cmp::max(dist, max);
-        tot += dist;
-    }
-
-    println!("Tree depth = {}", max);
-    println!("Total number of orbitals (part 1) = {}", tot);
-    println!("You orbit {}", map.get("YOU").unwrap());
-    println!("Santa orbits {}", map.get("SAN").unwrap());
-    println!("Distance to Santa (part 2) = {}", find_santa(&map));
+    {
+        // Asserts
+        let example = lines("COM)B B)C C)D D)E E)F B)G G)H D)I E)J J)K K)L");
+        let tree = OrbitTree::parse(&example).unwrap();
+        assert_eq!(tree.total_orbits(), 42);
+        assert_eq!(tree.depth("D"), Ok(3));
+        assert_eq!(tree.depth("L"), Ok(7));
+        assert_eq!(tree.depth("COM"), Ok(0));
+        assert_eq!(tree.lca("L", "I"), Ok("D"));
+        assert_eq!(tree.lca("H", "F"), Ok("B"));
+        assert_eq!(tree.lca("E", "L"), Ok("E"));
+        assert_eq!(tree.path("F", "H"), Ok(vec!["F", "E", "D", "C", "B", "G", "H"]));
+        assert_eq!(tree.path("C", "C"), Ok(vec!["C"]));
+        assert!(tree.depth("X").is_err());
+
+        let example = lines("COM)B B)C C)D D)E E)F B)G G)H D)I E)J J)K K)L K)YOU I)SAN");
+        let tree = OrbitTree::parse(&example).unwrap();
+        assert_eq!(tree.transfers("YOU", "SAN"), Ok(4));
+
+        assert!(OrbitTree::parse(&lines("COM)A A)B X)Y")).is_err()); // Two roots
+        assert!(OrbitTree::parse(&lines("COM)A A)B B)C C)A")).is_err()); // A orbits two bodies
+        assert!(OrbitTree::parse(&lines("COM)A B)C C)D D)B")).is_err()); // Cycle
+        assert!(OrbitTree::parse(&lines("A)B B)A")).is_err()); // No root
+        assert!(OrbitTree::parse(&lines("COM-A")).is_err());
+    }
+
+    let tree = OrbitTree::from_file("input.txt").unwrap();
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", tree.to_dot());
+        return;
+    }
+    println!("Tree depth = {}", tree.max_depth());
+    println!("Root = {}", tree.root());
+    {
+        // Part 1
+        println!("Part 1: Ans is: {}", tree.total_orbits());
+    }
+    {
+        // Part 2
+        println!("You orbit {}", tree.parent("YOU").unwrap().unwrap());
+        println!("Santa orbits {}", tree.parent("SAN").unwrap().unwrap());
+        println!("Part 2: Ans is: {}", tree.transfers("YOU", "SAN").unwrap());
+    }
 }
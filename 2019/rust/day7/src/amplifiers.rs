@@ -0,0 +1,59 @@
+use intcode::machine::*;
+use crate::permutations::permutations;
+use rayon::prelude::*;
+
+const STEP_BUDGET: usize = 100_000; // Instructions each amplifier may run before it is given up on
+
+#[derive(Copy, Clone, PartialEq, Debug)] // Debug printable
+pub enum Topology
+{
+    Linear,   // Each amplifier runs once and feeds the next, the last one drives the thrusters
+    Feedback, // The last amplifier also feeds the first, until the last one halts
+}
+
+// The thruster signal from a chain of amplifiers running `prog', one per phase setting. `None'
+// when an amplifier stops without an output, fails, or runs past its step budget.
+pub fn run_amplifiers(prog: &Machine, phases: &[isize], topology: Topology) -> Option<isize>
+{
+    let mut amps: Vec<Machine> = phases
+        .iter()
+        .map(|phase| {
+            let mut m = prog.clone();
+            m.set_step_limit(Some(STEP_BUDGET));
+            m.put_input(*phase);
+            m
+        })
+        .collect();
+    let mut signal: isize = 0;
+    loop
+    {
+        for m in amps.iter_mut()
+        {
+            m.put_input(signal);
+            match run_machine(m)
+            {
+                RunResult::Halted | RunResult::WaitingForInput => (),
+                _ => return None,
+            }
+            signal = m.get_output()?;
+        }
+        if topology == Topology::Linear || amps.last().is_none_or(|m| m.is_halted())
+        {
+            return Some(signal);
+        }
+    }
+}
+
+// The order of `phase_set' that gives the strongest signal, and the signal. Every order runs on
+// its own copies of the program, in parallel, and orders where `run_amplifiers' fails are skipped.
+pub fn best_setting(prog: &Machine, phase_set: &[isize], topology: Topology) -> Option<(Vec<isize>, isize)>
+{
+    let candidates: Vec<Vec<isize>> = permutations(phase_set).collect();
+    candidates
+        .into_par_iter()
+        .filter_map(|phases| {
+            let signal = run_amplifiers(prog, &phases, topology)?;
+            Some((phases, signal))
+        })
+        .max_by_key(|(_, signal)| *signal)
+}
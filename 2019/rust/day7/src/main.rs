@@ -1,27 +1,16 @@
-use num_traits::pow;
-pub mod machine;
-use machine::*;
-use std::cmp;
+pub mod amplifiers;
+pub mod permutations;
+use amplifiers::*;
+use intcode::machine::*;
+use permutations::*;
+use std::collections::HashSet;
 
-fn get_phase_settings(a: usize) -> [usize;5]
+fn program(s: &str) -> Machine
 {
-    [
-        (a / pow(5,0)) % 5,
-        (a / pow(5,1)) % 5,
-        (a / pow(5,2)) % 5,
-        (a / pow(5,3)) % 5,
-        (a / pow(5,4)) % 5,
-    ]
-}
-
-fn check_phase_settings(sett: [usize;5]) -> bool
-{
-    for a in 0..sett.len() {
-        for b in a+1..sett.len() {
-            if sett[a] == sett[b] { return false; }
-        }
-    }
-    true
+    let ints: Vec<isize> = s.split(',').map(|i| i.parse().expect("Unable to parse int")).collect();
+    let mut m: Machine = Machine::new();
+    load_machine_from_slice(&mut m, &ints);
+    m
 }
 
 fn main() {
@@ -31,61 +20,69 @@ fn main() {
     println!("Num ints read = {}", ints);
 
     // Part 1
-    let mut max: isize = 0;
-    for session_number in 0..(pow(5,5))
-    {
-        let phase_settings = get_phase_settings(session_number);
-        if !check_phase_settings(phase_settings) { continue }
-        let mut input: isize = 0;
-        for machine_number in 0..5
-        {
-            let mut m: Machine = Machine::new();
-            m.memcpy(&prog);
-            m.put_input(phase_settings[machine_number] as isize);
-            m.put_input(input);
-            run_machine(&mut m);
-            input = m.get_output().unwrap();
-        }
-        max = cmp::max(input, max);
-    }
+    let (phases, max) = best_setting(&prog, &[0, 1, 2, 3, 4], Topology::Linear).unwrap();
+    println!("Phase settings (part 1) = {:?}", phases);
     println!("Max (part 1) = {}", max);
     assert!(max == 199988);
 
     // Part 2
-    let mut max: isize = 0;
-    for session_number in 0..(pow(5,5))
-    {
-        let mut phase_settings = get_phase_settings(session_number);
-        if !check_phase_settings(phase_settings) { continue }
-        for a in &mut phase_settings { *a += 5 }
-        // println!("New phase settings: {:?}", phase_settings);
-        let mut input: isize = 0;
-        let mut m: [Machine; 5] = [
-            Machine::new(),
-            Machine::new(),
-            Machine::new(),
-            Machine::new(),
-            Machine::new(),
-        ];
-        for machine_number in 0..5
-        {
-            m[machine_number].memcpy(&prog);
-            m[machine_number].put_input(phase_settings[machine_number] as isize);
-        }
-        while !m[4].is_halted() {
-            for machine_number in 0..5
-            {
-                m[machine_number].put_input(input);
-                run_machine(&mut m[machine_number]);
-                input = m[machine_number].get_output().unwrap();
-            }
-        }
-        max = cmp::max(input, max);
-    }
+    let (phases, max) = best_setting(&prog, &[5, 6, 7, 8, 9], Topology::Feedback).unwrap();
+    println!("Phase settings (part 2) = {:?}", phases);
     println!("Max (part 2) = {}", max);
     assert!(max == 17519904);
 }
 
 fn run_asserts() {
-    assert!(get_phase_settings(0) == [0,0,0,0,0]);
+    // Every ordering exactly once
+    let all: Vec<Vec<usize>> = permutations(&[0, 1, 2, 3, 4]).collect();
+    assert!(all.len() == 120);
+    assert!(all.iter().collect::<HashSet<_>>().len() == 120);
+    assert!(all[0] == vec![0, 1, 2, 3, 4]);
+    assert!(all.iter().all(|p| { let mut p = p.clone(); p.sort_unstable(); p == vec![0, 1, 2, 3, 4] }));
+    for pair in all.windows(2)
+    {
+        assert!(pair[0].iter().zip(&pair[1]).filter(|(a, b)| a != b).count() == 2); // One swap apart
+    }
+    assert!(permutations::<usize>(&[]).count() == 1);
+
+    assert!(combinations(&[1, 2, 3, 4], 2).collect::<Vec<_>>() == vec![vec![1, 2], vec![1, 3], vec![1, 4], vec![2, 3], vec![2, 4], vec![3, 4]]);
+    assert!(combinations(&[1, 2, 3], 0).collect::<Vec<_>>() == vec![Vec::<usize>::new()]);
+    assert!(combinations(&[1, 2, 3], 4).count() == 0);
+
+    let some: Vec<Vec<usize>> = k_permutations(&[0, 1, 2, 3, 4], 2).collect();
+    assert!(some.len() == 20);
+    assert!(some.iter().collect::<HashSet<_>>().len() == 20);
+    assert!(some.iter().all(|p| p[0] != p[1]));
+
+    let repeated: Vec<Vec<usize>> = with_replacement(&[0, 1, 2, 3, 4], 5).collect();
+    assert!(repeated.len() == 3125);
+    assert!(repeated[0] == vec![0, 0, 0, 0, 0]);
+    assert!(repeated[7] == vec![0, 0, 0, 1, 2]);
+    assert!(repeated.iter().filter(|p| p.iter().collect::<HashSet<_>>().len() == 5).count() == 120);
+    assert!(with_replacement::<usize>(&[], 2).count() == 0);
+
+    // Examples from the puzzle
+    let prog = program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+    assert!(run_amplifiers(&prog, &[4, 3, 2, 1, 0], Topology::Linear) == Some(43210));
+    assert!(best_setting(&prog, &[0, 1, 2, 3, 4], Topology::Linear) == Some((vec![4, 3, 2, 1, 0], 43210)));
+    let prog = program("3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0");
+    assert!(best_setting(&prog, &[0, 1, 2, 3, 4], Topology::Linear) == Some((vec![0, 1, 2, 3, 4], 54321)));
+    let prog = program("3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0");
+    assert!(best_setting(&prog, &[0, 1, 2, 3, 4], Topology::Linear) == Some((vec![1, 0, 4, 3, 2], 65210)));
+
+    let prog = program("3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5");
+    assert!(run_amplifiers(&prog, &[9, 8, 7, 6, 5], Topology::Feedback) == Some(139629729));
+    assert!(best_setting(&prog, &[5, 6, 7, 8, 9], Topology::Feedback) == Some((vec![9, 8, 7, 6, 5], 139629729)));
+    let prog = program("3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10");
+    assert!(best_setting(&prog, &[5, 6, 7, 8, 9], Topology::Feedback) == Some((vec![9, 7, 8, 5, 6], 18216)));
+
+    // Amplifiers that give up: no output, and a spin forever unless phase + 2 * signal is non-zero
+    let prog = program("3,0,3,0,99");
+    assert!(run_amplifiers(&prog, &[0, 1], Topology::Linear).is_none());
+    assert!(best_setting(&prog, &[0, 1], Topology::Linear).is_none());
+    let prog = program("3,100,3,101,1002,101,2,102,1,102,100,102,1005,102,18,1105,1,15,4,102,99");
+    assert!(run_amplifiers(&prog, &[0, 1, 2], Topology::Linear).is_none());
+    assert!(run_amplifiers(&prog, &[0, 1, 2], Topology::Feedback).is_none());
+    assert!(run_amplifiers(&prog, &[1, 0, 2], Topology::Linear) == Some(6));
+    assert!(best_setting(&prog, &[0, 1, 2], Topology::Linear) == Some((vec![2, 1, 0], 10)));
 }
@@ -0,0 +1,134 @@
+// Orderings and selections of a list of items, each yielded as a new Vec
+
+// Every ordering of all the items, by Heap's algorithm so each one is a single swap from the last
+pub struct Permutations<T>
+{
+    items: Vec<T>,
+    counters: Vec<usize>, // The loop counters of the recursive version
+    i: usize,
+    first: bool,
+}
+
+impl<T: Clone> Iterator for Permutations<T>
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>>
+    {
+        if self.first
+        {
+            self.first = false;
+            return Some(self.items.clone());
+        }
+        while self.i < self.items.len()
+        {
+            if self.counters[self.i] < self.i
+            {
+                if self.i & 1 == 0
+                {
+                    self.items.swap(0, self.i);
+                }
+                else
+                {
+                    self.items.swap(self.counters[self.i], self.i);
+                }
+                self.counters[self.i] += 1;
+                self.i = 1;
+                return Some(self.items.clone());
+            }
+            self.counters[self.i] = 0;
+            self.i += 1;
+        }
+        None
+    }
+}
+
+pub fn permutations<T: Clone>(items: &[T]) -> Permutations<T>
+{
+    Permutations { items: items.to_vec(), counters: vec![0; items.len()], i: 1, first: true }
+}
+
+// Every choice of `k' of the items in their original order, in lexicographic order of positions
+pub struct Combinations<T>
+{
+    items: Vec<T>,
+    indices: Option<Vec<usize>>, // The next choice, None when done
+}
+
+impl<T: Clone> Iterator for Combinations<T>
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>>
+    {
+        let items = &self.items;
+        let indices = self.indices.as_mut()?;
+        let out = indices.iter().map(|i| items[*i].clone()).collect();
+        let n = items.len();
+        let k = indices.len();
+        // The rightmost index that can still move right, then pack the ones after it behind it
+        match (0..k).rev().find(|j| indices[*j] < n - k + j)
+        {
+            Some(j) =>
+            {
+                indices[j] += 1;
+                for next in j + 1..k
+                {
+                    indices[next] = indices[next - 1] + 1;
+                }
+            }
+            None => self.indices = None,
+        }
+        Some(out)
+    }
+}
+
+pub fn combinations<T: Clone>(items: &[T], k: usize) -> Combinations<T>
+{
+    let indices = if k <= items.len() { Some((0..k).collect()) } else { None };
+    Combinations { items: items.to_vec(), indices }
+}
+
+// Every ordering of every choice of `k' of the items
+pub fn k_permutations<T: Clone>(items: &[T], k: usize) -> impl Iterator<Item = Vec<T>>
+{
+    combinations(items, k).flat_map(|chosen| permutations(&chosen))
+}
+
+// Every sequence of `k' items where items may repeat, counting with the last position fastest
+pub struct WithReplacement<T>
+{
+    items: Vec<T>,
+    indices: Option<Vec<usize>>,
+}
+
+impl<T: Clone> Iterator for WithReplacement<T>
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>>
+    {
+        let items = &self.items;
+        let indices = self.indices.as_mut()?;
+        let out = indices.iter().map(|i| items[*i].clone()).collect();
+        match (0..indices.len()).rev().find(|j| indices[*j] + 1 < items.len())
+        {
+            Some(j) =>
+            {
+                indices[j] += 1;
+                for index in indices.iter_mut().skip(j + 1)
+                {
+                    *index = 0;
+                }
+            }
+            None => self.indices = None,
+        }
+        Some(out)
+    }
+}
+
+pub fn with_replacement<T: Clone>(items: &[T], k: usize) -> WithReplacement<T>
+{
+    let indices = if k == 0 || !items.is_empty() { Some(vec![0; k]) } else { None };
+    WithReplacement { items: items.to_vec(), indices }
+}
@@ -1,86 +1,71 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fs;
+
+pub mod sif;
+use sif::{SifImage, LETTER_HEIGHT};
 
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
-const PIXELS_PER_LAYER: usize = WIDTH * HEIGHT;
 
-#[derive(Clone, PartialEq)]
-struct Layer {
-    pixels: Vec<usize>,
-}
-
-fn count_digit(l: &Layer, d: usize) -> usize {
-    let mut count = 0;
-    for p in l.pixels.clone() {
-        if p == d {
-            count += 1;
-        }
-    }
-    count
+// The layer with the fewest 0 digits, its 1 digits times its 2 digits
+fn checksum(image: &SifImage) -> usize {
+    let layer = image.layer_with_fewest(sif::BLACK);
+    SifImage::count(layer, sif::WHITE) * SifImage::count(layer, sif::TRANSPARENT)
 }
 
+// Usage: day8 [--png <file>]
 fn main() {
-    let mut layers: Vec<Layer> = Vec::new();
-    let f = File::open("input.txt").expect("Unable to open file");
-    let f = BufReader::new(f);
-
-    // Scan the lines of the file
-    let mut digits: Vec<usize> = Vec::new();
-    for line in f.lines() {
-        let line = line.expect("Unable to read line");
-        let mut v: Vec<_> = line
-            .chars()
-            .map(|d| d.to_digit(10).unwrap() as usize)
-            .collect();
-        digits.append(&mut v);
-    }
+    {
+        // Asserts
+        let image = SifImage::decode("123456789012", 3, 2);
+        assert!(image.is_err()); // Digits above 2 are not pixels
+        let image = SifImage::decode("0222112222120000", 2, 2).unwrap();
+        assert_eq!(image.layers.len(), 4);
+        assert_eq!(image.composite(), vec![0, 1, 1, 0]);
+        assert_eq!(image.render(), " █\n█ \n");
+        assert_eq!(image.encode(), "0222112222120000");
+        assert!(SifImage::decode("02221", 2, 2).is_err());
+        assert!(SifImage::decode("", 2, 2).is_err());
 
-    println!("Pixels per layer: {:?}", PIXELS_PER_LAYER);
-    println!("Pixels tot: {:?}", digits.len());
-    println!("Number of layers: {:?}", digits.len() / PIXELS_PER_LAYER);
-
-    for chunk in digits.chunks_mut(PIXELS_PER_LAYER) {
-        layers.push(Layer {
-            pixels: chunk.to_vec(),
-        });
-    }
-    let mut min_layer: Layer = Layer { pixels: Vec::new() };
-    let mut layer_count: usize = std::usize::MAX;
-    for layer in layers.clone() {
-        let count = count_digit(&layer, 0);
-        if count < layer_count {
-            layer_count = count;
-            min_layer = layer;
+        // Generated images show the bitmap and survive a round trip
+        let bitmap = sif::text_to_bitmap("HGBCF").unwrap();
+        let bitmap: Vec<&str> = bitmap.iter().map(|row| row.as_str()).collect();
+        for layers in [1, 3, 10].iter() {
+            let image = SifImage::from_bitmap(&bitmap, *layers);
+            assert_eq!(image.layers.len(), *layers);
+            assert_eq!(
+                SifImage::decode(&image.encode(), image.width, image.height),
+                Ok(image.clone())
+            );
+            assert_eq!(image.ocr(), Ok("HGBCF".to_string()));
         }
+        let alphabet = "ABCEFGHJKLOPRSUYZ";
+        let bitmap = sif::text_to_bitmap(alphabet).unwrap();
+        let bitmap: Vec<&str> = bitmap.iter().map(|row| row.as_str()).collect();
+        let image = SifImage::from_bitmap(&bitmap, 5);
+        assert_eq!(image.height, LETTER_HEIGHT);
+        assert_eq!(image.ocr(), Ok(alphabet.to_string()));
+        assert!(sif::text_to_bitmap("X").is_err());
+        assert!(SifImage::from_bitmap(&["#"; 6], 1).ocr().is_err());
     }
-    let part1 = count_digit(&min_layer, 1) * count_digit(&min_layer, 2);
-    println!("Part 1: {}", part1);
-    assert_eq!(part1, 2904);
-
-    let mut image: Vec<char> = vec![' '; PIXELS_PER_LAYER];
-
-    layers.reverse(); // render from back to front
 
-    let mut offset: usize;
-    for layer in layers {
-        offset = 0;
-        for pixel in layer.pixels {
-            match pixel {
-                0 => {image[offset] = ' ';}
-                1 => {image[offset] = '*';}
-                _ => {}
-            }
-            offset += 1;
-        }
+    let data = fs::read_to_string("input.txt").expect("Unable to read input.txt");
+    let image = SifImage::decode(&data, WIDTH, HEIGHT).unwrap();
+    println!("Number of layers: {}", image.layers.len());
+    {
+        // Part 1
+        let part1 = checksum(&image);
+        assert_eq!(part1, 2904);
+        println!("Part 1: Ans is: {}", part1);
+    }
+    {
+        // Part 2
+        print!("{}", image.render());
+        println!("Part 2: Ans is: {}", image.ocr().unwrap());
     }
 
-    print!("Part 2:");
-    for pixel in 0..image.len() {
-        if pixel % WIDTH == 0 {
-            print!("\n");
-        }
-        print!("{}", image[pixel]);
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(idx) = args.iter().position(|arg| arg == "--png") {
+        let file = args.get(idx + 1).expect("Usage: day8 [--png <file>]");
+        image.write_png(file, 10).unwrap();
     }
-    print!("\n");
 }
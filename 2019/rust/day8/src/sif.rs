@@ -0,0 +1,177 @@
+// Space Image Format: layers of width * height digits, the first layer is in front
+use std::fs::File;
+use std::io::BufWriter;
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+pub const TRANSPARENT: u8 = 2;
+
+pub use ocr::LETTER_HEIGHT;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SifImage {
+    pub width: usize,
+    pub height: usize,
+    pub layers: Vec<Vec<u8>>, // Front to back, width * height digits each
+}
+
+impl SifImage {
+    pub fn decode(data: &str, width: usize, height: usize) -> Result<SifImage, String> {
+        let size = width * height;
+        if size == 0 {
+            return Err("The image has no pixels".to_string());
+        }
+        let digits = data
+            .trim()
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .filter(|d| *d <= TRANSPARENT as u32)
+                    .map(|d| d as u8)
+                    .ok_or(format!("Bad pixel `{}'", c))
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        if digits.is_empty() || digits.len() % size != 0 {
+            return Err(format!(
+                "{} pixels do not make whole {}x{} layers",
+                digits.len(),
+                width,
+                height
+            ));
+        }
+        Ok(SifImage {
+            width,
+            height,
+            layers: digits.chunks(size).map(|chunk| chunk.to_vec()).collect(),
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        self.layers
+            .iter()
+            .flatten()
+            .map(|d| char::from(b'0' + d))
+            .collect()
+    }
+
+    // A stack of `layers' layers showing `bitmap' (`#' is white), with every pixel hidden behind a
+    // different number of transparent layers and the opposite colour behind it
+    pub fn from_bitmap(bitmap: &[&str], layers: usize) -> SifImage {
+        let height = bitmap.len();
+        let width = bitmap.iter().map(|row| row.len()).max().unwrap_or(0);
+        let layers = layers.max(1);
+        let mut out = vec![vec![TRANSPARENT; width * height]; layers];
+        for (y, row) in bitmap.iter().enumerate() {
+            for x in 0..width {
+                let colour = if row.as_bytes().get(x) == Some(&b'#') {
+                    WHITE
+                } else {
+                    BLACK
+                };
+                let i = y * width + x;
+                let front = (i * 7 + y) % layers; // First opaque layer
+                out[front][i] = colour;
+                for (depth, layer) in out.iter_mut().enumerate().skip(front + 1) {
+                    if (i + depth) % 3 != 0 {
+                        layer[i] = 1 - colour;
+                    }
+                }
+            }
+        }
+        SifImage {
+            width,
+            height,
+            layers: out,
+        }
+    }
+
+    pub fn count(layer: &[u8], digit: u8) -> usize {
+        layer.iter().filter(|d| **d == digit).count()
+    }
+
+    pub fn layer_with_fewest(&self, digit: u8) -> &[u8] {
+        self.layers
+            .iter()
+            .min_by_key(|layer| SifImage::count(layer, digit))
+            .unwrap()
+    }
+
+    // The first pixel that is not transparent, front to back
+    pub fn composite(&self) -> Vec<u8> {
+        (0..self.width * self.height)
+            .map(|i| {
+                self.layers
+                    .iter()
+                    .map(|layer| layer[i])
+                    .find(|d| *d != TRANSPARENT)
+                    .unwrap_or(TRANSPARENT)
+            })
+            .collect()
+    }
+
+    fn rows(&self) -> Vec<Vec<u8>> {
+        self.composite()
+            .chunks(self.width)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    // White is a full block, black and transparent are blank
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for row in self.rows() {
+            for pixel in row {
+                out.push(if pixel == WHITE { '█' } else { ' ' });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // Every pixel becomes a `scale' by `scale' square, transparent pixels stay transparent
+    pub fn write_png(&self, file: &str, scale: usize) -> Result<(), String> {
+        let scale = scale.max(1);
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut data = Vec::with_capacity(width * height * 4);
+        for row in self.rows() {
+            let mut line = Vec::with_capacity(width * 4);
+            for pixel in row {
+                let rgba = match pixel {
+                    WHITE => [255, 255, 255, 255],
+                    BLACK => [0, 0, 0, 255],
+                    _ => [0, 0, 0, 0],
+                };
+                for _ in 0..scale {
+                    line.extend_from_slice(&rgba);
+                }
+            }
+            for _ in 0..scale {
+                data.extend_from_slice(&line);
+            }
+        }
+        let f = File::create(file).map_err(|e| format!("Unable to create {}: {}", file, e))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(f), width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&data).map_err(|e| e.to_string())
+    }
+
+    // Reads the letters of the composited image
+    pub fn ocr(&self) -> Result<String, String> {
+        let rows: Vec<Vec<bool>> = self
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|pixel| *pixel == WHITE).collect())
+            .collect();
+        ocr::read(&rows)
+    }
+}
+
+// Draws `text' in the letters `ocr' reads, one string per row
+pub fn text_to_bitmap(text: &str) -> Result<Vec<String>, String> {
+    Ok(ocr::draw(text)?
+        .iter()
+        .map(|row| row.iter().map(|lit| if *lit { '#' } else { '.' }).collect())
+        .collect())
+}
@@ -0,0 +1,436 @@
+// Static analysis of a loaded Intcode program
+//
+// Finds the reachable instructions by following the control flow from position 0, and the
+// write targets that are known before running the program. Jumps through memory cannot be
+// followed exactly, their possible targets are approximated by the constants the program
+// stores (return addresses are pushed with `ADD #ret #0') and by the initial contents of the
+// cells they read the target from. Programs that jump through a table (day 7 picks its
+// amplifier routine by phase) may use any value stored outside the code as a target.
+//
+// The cells of the loaded program are then classified as code, data only read through position
+// mode parameters, data written through them, or unreachable. Relative mode accesses depend on
+// the run, cells only touched that way count as unreachable.
+use crate::machine::*;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub pos: usize,
+    pub id: isize,
+    pub param_modes: Vec<ParamMode>,
+}
+
+impl Instruction {
+    pub fn width(&self) -> usize {
+        self.param_modes.len() + 1
+    }
+    pub fn name(&self) -> &'static str {
+        opinfo_from_id(self.id).unwrap().name
+    }
+    // The parameter the instruction writes to, if any
+    pub fn write_param(&self) -> Option<usize> {
+        match self.name() {
+            "ADD" | "MULT" | "LESS" | "EQ" => Some(2),
+            "IN" => Some(0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CellClass {
+    Code,          // Part of a reachable instruction
+    ReadOnlyData,  // Read by a reachable instruction, never written directly
+    ReadWriteData, // Written directly by a reachable instruction
+    Unreachable,   // Not touched by any known instruction or access
+}
+
+impl CellClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CellClass::Code => "code",
+            CellClass::ReadOnlyData => "read-only data",
+            CellClass::ReadWriteData => "read-write data",
+            CellClass::Unreachable => "unreachable",
+        }
+    }
+}
+
+// A write whose target is known statically and lands on a reachable instruction
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CodeWrite {
+    pub writer: usize,      // Position of the writing instruction
+    pub target: usize,      // The written cell
+    pub instruction: usize, // Position of the instruction the cell belongs to
+    pub opcode: bool,       // The cell holds the operation and parameter modes, not a parameter
+    pub runs_again: bool,   // The overwritten instruction is reachable after the write
+}
+
+#[derive(Debug)]
+pub struct Analysis {
+    pub len: usize,
+    pub instructions: BTreeMap<usize, Instruction>, // Reachable instructions by position
+    pub illegal: BTreeSet<usize>, // Reachable positions that do not hold a valid operation
+    pub jump_targets: BTreeSet<usize>, // Known jump targets, and candidates for dynamic jumps
+    pub dynamic_jumps: BTreeSet<usize>, // Jumps whose target is read from memory
+    pub direct_writes: BTreeMap<usize, BTreeSet<usize>>, // Target -> writers, for position mode targets
+    pub dynamic_writes: BTreeSet<usize>,                 // Writers with a relative mode target
+    pub direct_reads: BTreeSet<usize>, // Cells read through position mode parameters
+    pub code_writes: Vec<CodeWrite>,
+    pub classes: Vec<CellClass>, // Class of every cell of the loaded program
+    successors: BTreeMap<usize, Vec<usize>>,
+}
+
+impl Analysis {
+    // Some instruction may be overwritten before it runs, compiling the program ahead of time is unsafe
+    pub fn is_self_modifying(&self) -> bool {
+        self.code_writes.iter().any(|w| w.opcode && w.runs_again)
+    }
+
+    // The reachable instruction covering `pos', if any
+    pub fn instruction_at(&self, pos: usize) -> Option<&Instruction> {
+        self.instructions
+            .range(..=pos)
+            .next_back()
+            .map(|(_, instr)| instr)
+            .filter(|instr| pos < instr.pos + instr.width())
+    }
+
+    // Cells past the loaded program are scratch memory for relative mode accesses
+    pub fn class_of(&self, pos: usize) -> CellClass {
+        match self.classes.get(pos) {
+            Some(class) => *class,
+            None if self.dynamic_writes.is_empty() => CellClass::Unreachable,
+            None => CellClass::ReadWriteData,
+        }
+    }
+
+    // Runs of cells with the same class, as (first, last, class)
+    pub fn regions(&self) -> Vec<(usize, usize, CellClass)> {
+        let mut regions: Vec<(usize, usize, CellClass)> = Vec::new();
+        for (pos, &class) in self.classes.iter().enumerate() {
+            match regions.last_mut() {
+                Some(last) if last.2 == class => last.1 = pos,
+                _ => regions.push((pos, pos, class)),
+            }
+        }
+        regions
+    }
+
+    pub fn successors(&self, pos: usize) -> &[usize] {
+        self.successors.get(&pos).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // All instructions reachable after executing the instruction at `pos'
+    fn reachable_after(&self, pos: usize) -> BTreeSet<usize> {
+        let mut seen = BTreeSet::new();
+        let mut queue: VecDeque<usize> = self.successors(pos).iter().copied().collect();
+        while let Some(next) = queue.pop_front() {
+            if seen.insert(next) {
+                queue.extend(self.successors(next));
+            }
+        }
+        seen
+    }
+}
+
+fn decode(m: &Machine, pos: usize) -> Option<Instruction> {
+    let op = split_opcode(*m.mem.get(pos)?)?;
+    if pos + op.param_modes.len() >= m.mem.len() {
+        return None;
+    }
+    Some(Instruction {
+        pos,
+        id: op.id,
+        param_modes: op.param_modes,
+    })
+}
+
+pub fn analyse(m: &Machine) -> Analysis {
+    // Programs guard code with conditions on cells that never change, like `JIF *0 #65' where
+    // position 0 holds an opcode. The first pass takes every branch, the second pass treats the
+    // cells no reachable instruction writes to as constants, which only removes branches.
+    let first = analyse_with_constants(m, &BTreeSet::new());
+    let constants = (0..m.len())
+        .filter(|pos| !first.direct_writes.contains_key(pos))
+        .collect();
+    analyse_with_constants(m, &constants)
+}
+
+fn analyse_with_constants(m: &Machine, constants: &BTreeSet<usize>) -> Analysis {
+    let mut a = Analysis {
+        len: m.len(),
+        instructions: BTreeMap::new(),
+        illegal: BTreeSet::new(),
+        jump_targets: BTreeSet::new(),
+        dynamic_jumps: BTreeSet::new(),
+        direct_writes: BTreeMap::new(),
+        dynamic_writes: BTreeSet::new(),
+        direct_reads: BTreeSet::new(),
+        code_writes: Vec::new(),
+        classes: Vec::new(),
+        successors: BTreeMap::new(),
+    };
+    let mut candidates: BTreeSet<usize> = BTreeSet::new(); // Possible targets of dynamic jumps
+    let mut operand_targets: BTreeSet<usize> = BTreeSet::new(); // Initial values of jump operands
+    let mut table_jumps = false; // Some jump reads its target from memory in position mode
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    queue.push_back(0);
+
+    // Follow the control flow, adding the candidates once everything else is found
+    loop {
+        while let Some(pos) = queue.pop_front() {
+            if pos >= a.len || a.instructions.contains_key(&pos) || a.illegal.contains(&pos) {
+                continue; // Running past the program ends the run, like `run_machine'
+            }
+            let instr = match decode(m, pos) {
+                Some(instr) => instr,
+                None => {
+                    a.illegal.insert(pos);
+                    continue;
+                }
+            };
+            let param = |idx: usize| m.mem[pos + idx + 1];
+            let next = pos + instr.width();
+            let mut successors = Vec::new();
+            match instr.name() {
+                "HALT" => {}
+                "JIT" | "JIF" => {
+                    let jump_if_true = instr.name() == "JIT";
+                    // A constant condition makes the jump unconditional, or never taken
+                    let condition = match instr.param_modes[0] {
+                        ParamMode::Immediate if constants.contains(&(pos + 1)) => Some(param(0)),
+                        ParamMode::Indirect if constants.contains(&(param(0) as usize)) => {
+                            Some(m.mem[param(0) as usize])
+                        }
+                        _ => None,
+                    };
+                    let (taken, not_taken) = match condition {
+                        Some(condition) => {
+                            let taken = (condition != 0) == jump_if_true;
+                            (taken, !taken)
+                        }
+                        None => (true, true),
+                    };
+                    if not_taken {
+                        successors.push(next);
+                    }
+                    if taken {
+                        match instr.param_modes[1] {
+                            ParamMode::Immediate if param(1) >= 0 => {
+                                a.jump_targets.insert(param(1) as usize);
+                                successors.push(param(1) as usize);
+                            }
+                            ParamMode::Indirect => {
+                                a.dynamic_jumps.insert(pos);
+                                table_jumps = true;
+                                // A table of jump targets, or a target computed before the jump
+                                if let Some(&target) = m.mem.get(param(1) as usize) {
+                                    if target >= 0 {
+                                        candidates.insert(target as usize);
+                                        operand_targets.insert(target as usize);
+                                    }
+                                }
+                            }
+                            _ => {
+                                a.dynamic_jumps.insert(pos);
+                            }
+                        }
+                    }
+                }
+                "ADD" | "MULT" => {
+                    // Storing a constant, `ADD #v #0' or `MULT #v #1', is how code addresses are
+                    // pushed. Constants used in arithmetic are more likely data addresses.
+                    let identity = if instr.name() == "ADD" { 0 } else { 1 };
+                    let immediate = |idx: usize| {
+                        Some(param(idx)).filter(|_| instr.param_modes[idx] == ParamMode::Immediate)
+                    };
+                    for (value, other) in &[(0, 1), (1, 0)] {
+                        if let (Some(value), Some(other)) = (immediate(*value), immediate(*other)) {
+                            if other == identity && value >= 0 {
+                                candidates.insert(value as usize);
+                            }
+                        }
+                    }
+                    successors.push(next);
+                }
+                _ => successors.push(next),
+            }
+            for (idx, mode) in instr.param_modes.iter().enumerate() {
+                if *mode == ParamMode::Indirect
+                    && Some(idx) != instr.write_param()
+                    && param(idx) >= 0
+                {
+                    a.direct_reads.insert(param(idx) as usize);
+                }
+            }
+            if let Some(idx) = instr.write_param() {
+                match instr.param_modes[idx] {
+                    ParamMode::Indirect if param(idx) >= 0 => {
+                        a.direct_writes
+                            .entry(param(idx) as usize)
+                            .or_default()
+                            .insert(pos);
+                    }
+                    _ => {
+                        a.dynamic_writes.insert(pos);
+                    }
+                }
+            }
+            queue.extend(successors.iter().copied());
+            a.successors.insert(pos, successors);
+            a.instructions.insert(pos, instr);
+        }
+
+        if a.dynamic_jumps.is_empty() {
+            break;
+        }
+        // Only candidates that start a valid instruction without overlapping known code, one at
+        // a time since following one candidate may reveal that another is inside an instruction.
+        // The initial value of a jump operand may also be the start of a known instruction.
+        let valid_candidate = |a: &Analysis, candidate: usize, known: bool| {
+            candidate < a.len
+                && !a.jump_targets.contains(&candidate)
+                && match a.instruction_at(candidate) {
+                    Some(instr) => known && instr.pos == candidate,
+                    None => decode(m, candidate).is_some(),
+                }
+        };
+        let mut next = candidates.iter().copied().find(|&candidate| {
+            valid_candidate(&a, candidate, operand_targets.contains(&candidate))
+        });
+        if next.is_none() && table_jumps {
+            // Weaker candidates only once the stronger ones are exhausted, they may be plain data
+            next = (0..a.len)
+                .filter(|&pos| a.instruction_at(pos).is_none() && m.mem[pos] >= 0)
+                .map(|pos| m.mem[pos] as usize)
+                .find(|&candidate| valid_candidate(&a, candidate, false));
+        }
+        match next {
+            Some(candidate) => {
+                a.jump_targets.insert(candidate);
+                queue.push_back(candidate);
+            }
+            None => break,
+        }
+    }
+
+    // Dynamic jumps may go to any candidate
+    let dynamic_targets: Vec<usize> = a
+        .jump_targets
+        .iter()
+        .copied()
+        .filter(|target| a.instructions.contains_key(target))
+        .collect();
+    for pos in &a.dynamic_jumps {
+        a.successors
+            .get_mut(pos)
+            .unwrap()
+            .extend(dynamic_targets.iter().copied());
+    }
+
+    // Direct writes into reachable instructions, or into reachable cells that are not valid yet
+    let mut code_writes = Vec::new();
+    for (&target, writers) in &a.direct_writes {
+        let instruction = match a.instruction_at(target) {
+            Some(instr) => instr.pos,
+            None if a.illegal.contains(&target) => target,
+            None => continue,
+        };
+        for &writer in writers {
+            code_writes.push(CodeWrite {
+                writer,
+                target,
+                instruction,
+                opcode: target == instruction,
+                runs_again: a.reachable_after(writer).contains(&instruction),
+            });
+        }
+    }
+    a.code_writes = code_writes;
+
+    let mut classes = vec![CellClass::Unreachable; a.len];
+    for &pos in &a.direct_reads {
+        if let Some(class) = classes.get_mut(pos) {
+            *class = CellClass::ReadOnlyData;
+        }
+    }
+    for &pos in a.direct_writes.keys() {
+        if let Some(class) = classes.get_mut(pos) {
+            *class = CellClass::ReadWriteData;
+        }
+    }
+    for instr in a.instructions.values() {
+        let end = (instr.pos + instr.width()).min(a.len);
+        for class in &mut classes[instr.pos..end] {
+            *class = CellClass::Code;
+        }
+    }
+    for &pos in &a.illegal {
+        classes[pos] = CellClass::Code;
+    }
+    a.classes = classes;
+    a
+}
+
+// Disassembly guided by the analysis, reachable instructions are decoded and every other cell
+// of the program is listed on its own as `DATA'
+pub fn disassemble_reachable(m: &Machine, a: &Analysis) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < a.len {
+        match a.instructions.get(&pos) {
+            Some(instr) => {
+                out.push((pos, machine_op_at_to_string(m, pos).0));
+                pos += instr.width();
+            }
+            None => {
+                out.push((pos, format!("DATA {}", m.mem[pos])));
+                pos += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyse_slice(program: &[isize]) -> Analysis {
+        let mut m = Machine::new();
+        load_machine_from_slice(&mut m, program);
+        analyse(&m)
+    }
+
+    #[test]
+    fn test_classify_day2_example() {
+        let a = analyse_slice(&[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+        assert_eq!(
+            a.regions(),
+            vec![(0, 8, CellClass::Code), (9, 11, CellClass::ReadOnlyData)]
+        );
+        // Both writes land in code that never runs again
+        assert_eq!(a.code_writes.len(), 2);
+        assert!(!a.is_self_modifying());
+    }
+
+    #[test]
+    fn test_detect_self_modifying() {
+        // Multiplies the 33 at position 4 into the HALT it then runs
+        let a = analyse_slice(&[1002, 4, 3, 4, 33]);
+        assert!(a.is_self_modifying());
+        assert_eq!(a.class_of(4), CellClass::Code);
+    }
+
+    #[test]
+    fn test_classify_data() {
+        // Adds the constant at 9 to the counter at 10 and outputs it, cell 11 is never touched
+        let a = analyse_slice(&[1, 9, 10, 10, 4, 10, 99, 0, 0, 5, 0, 0]);
+        assert_eq!(a.class_of(7), CellClass::Unreachable);
+        assert_eq!(a.class_of(9), CellClass::ReadOnlyData);
+        assert_eq!(a.class_of(10), CellClass::ReadWriteData);
+        assert_eq!(a.class_of(11), CellClass::Unreachable);
+        assert_eq!(a.class_of(100), CellClass::Unreachable);
+    }
+}
@@ -0,0 +1,71 @@
+// Prints the static analysis of an Intcode program
+//
+// Usage: intcode-analyse <program> [--listing]
+//
+// Shows the memory regions by class, the jumps through memory and every write into code.
+// With `--listing' the disassembly of the reachable code follows, with data cells on their own.
+
+use intcode::analysis::*;
+use intcode::machine::*;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <program> [--listing]", args[0]);
+        std::process::exit(1);
+    }
+    let mut m = Machine::new();
+    load_machine_from_file(&mut m, &args[1]);
+    let a = analyse(&m);
+
+    println!("{}: {} ints", args[1], a.len);
+    println!(
+        "{} reachable instructions, {} illegal positions",
+        a.instructions.len(),
+        a.illegal.len()
+    );
+    println!("Regions:");
+    for (first, last, class) in a.regions() {
+        println!(
+            "{:>7}..={:<7} {:>5}  {}",
+            first,
+            last,
+            last - first + 1,
+            class.name()
+        );
+    }
+    if !a.dynamic_jumps.is_empty() {
+        println!("Jumps through memory at {:?}", a.dynamic_jumps);
+    }
+    if !a.dynamic_writes.is_empty() {
+        println!(
+            "Writes through relative addresses at {:?}",
+            a.dynamic_writes
+        );
+    }
+    for w in &a.code_writes {
+        println!(
+            "Code write: {} writes {} in the instruction at {}{}{}",
+            w.writer,
+            w.target,
+            w.instruction,
+            if w.opcode { ", its operation" } else { "" },
+            if w.runs_again {
+                ", which runs again"
+            } else {
+                ""
+            }
+        );
+    }
+    println!(
+        "Self-modifying: {}",
+        if a.is_self_modifying() { "yes" } else { "no" }
+    );
+
+    if args.iter().any(|arg| arg == "--listing") {
+        println!();
+        for (pos, text) in disassemble_reachable(&m, &a) {
+            println!("{:>7}: {:<40} {}", pos, text, a.class_of(pos).name());
+        }
+    }
+}
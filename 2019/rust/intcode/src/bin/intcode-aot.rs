@@ -0,0 +1,55 @@
+// Compiles an Intcode program ahead of time into a Rust module
+//
+// Usage: intcode-aot <program> [--main] [-o <output.rs>]
+//
+// With `--main' the module gets a `main' running the program with inputs from the command
+// line, printing the same as `intcode-run'. Self modifying programs are refused.
+
+use intcode::machine::*;
+use intcode::transpile::*;
+use std::io::Write;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut program = None;
+    let mut output = None;
+    let mut with_main = false;
+    let mut idx = 1;
+    while idx < args.len() {
+        match args[idx].as_str() {
+            "--main" => with_main = true,
+            "-o" => {
+                idx += 1;
+                output = args.get(idx).cloned();
+            }
+            arg => program = Some(arg.to_string()),
+        }
+        idx += 1;
+    }
+    let program = match program {
+        Some(program) => program,
+        None => {
+            eprintln!("Usage: {} <program> [--main] [-o <output.rs>]", args[0]);
+            std::process::exit(1);
+        }
+    };
+
+    let mut m = Machine::new();
+    load_machine_from_file(&mut m, &program);
+    let mut code = match transpile(&m, &program) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if with_main {
+        code += &harness();
+    }
+    match output {
+        Some(output) => std::fs::write(&output, code).expect("Unable to write output"),
+        None => {
+            std::io::stdout().write_all(code.as_bytes()).ok();
+        }
+    }
+}
@@ -0,0 +1,586 @@
+// Debug Adapter Protocol server for Intcode programs, speaks DAP over stdin/stdout
+//
+// Launch arguments:
+//   program     - path to a comma separated Intcode file
+//   inputs      - list of integers queued as input before the program starts
+//   stopOnEntry - stop before the first instruction
+//
+// The program is shown as a disassembly listing, one instruction per line, so line
+// breakpoints are set in that listing. Instruction breakpoints take plain addresses, one that
+// does not start an instruction moves to the next instruction and is reported there.
+// In the debug console `input 1 2 3' queues more input and `mem[123]', `pc' or `rb' reads state.
+// `continue' runs RUN_SLICE instructions at a time and handles requests in between, so `pause'
+// and `disconnect' work on a program that runs for long or never stops.
+// Memory cells carry their class from the static analysis (code, read-only data, ...) as type.
+
+use intcode::analysis::*;
+use intcode::machine::*;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{channel, TryRecvError};
+
+const THREAD_ID: u64 = 1;
+const SOURCE_REFERENCE: u64 = 1;
+const REGISTERS_REFERENCE: u64 = 1;
+const MEMORY_REFERENCE: u64 = 2;
+const MEMORY_ROW_REFERENCE_BASE: u64 = 1000; // Memory row N is variables reference BASE + N
+const MEMORY_ROW_WIDTH: usize = 10;
+const RUN_SLICE: usize = 100_000; // Instructions run between checks for new requests
+
+struct Adapter {
+    m: Machine,
+    seq: u64,
+    program: String,
+    listing: Vec<(usize, String)>, // The disassembly shown as source, line N is listing[N - 1]
+    analysis: Option<Analysis>,    // Of the program as loaded
+    line_breakpoints: BTreeSet<usize>,
+    instruction_breakpoints: BTreeSet<usize>,
+    stop_on_entry: bool,
+    running: bool,                // A `continue' is in progress, run a slice at a time
+    reported_outputs: usize,      // Outputs already sent to the debug console
+    events: Vec<(String, Value)>, // Events to send after the current response
+}
+
+fn read_message(input: &mut impl BufRead) -> Option<Value> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None; // The client went away
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_length = len.trim().parse::<usize>().ok();
+        }
+    }
+    let mut buf = vec![0; content_length?];
+    input.read_exact(&mut buf).ok()?;
+    serde_json::from_slice(&buf).ok()
+}
+
+fn write_message(message: &Value) {
+    let text = message.to_string();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    write!(out, "Content-Length: {}\r\n\r\n{}", text.len(), text).ok();
+    out.flush().ok();
+}
+
+fn parse_address(s: &str) -> Option<usize> {
+    let s = s.trim();
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn load_program(file: &str) -> Result<Machine, String> {
+    // Validate up front, `load_machine_from_file' exits the process on bad input
+    let text =
+        std::fs::read_to_string(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+    for split in text.split([',', '\n']).map(str::trim) {
+        if !split.is_empty() && split.parse::<isize>().is_err() {
+            return Err(format!("Unable to parse `{}' in {}", split, file));
+        }
+    }
+    let mut m = Machine::new();
+    load_machine_from_file(&mut m, file);
+    Ok(m)
+}
+
+impl Adapter {
+    fn new() -> Self {
+        Adapter {
+            m: Machine::new(),
+            seq: 0,
+            program: String::new(),
+            listing: Vec::new(),
+            analysis: None,
+            line_breakpoints: BTreeSet::new(),
+            instruction_breakpoints: BTreeSet::new(),
+            stop_on_entry: false,
+            running: false,
+            reported_outputs: 0,
+            events: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&message);
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        self.events.push((event.to_string(), body));
+    }
+
+    fn stopped(&mut self, reason: &str, text: Option<String>) {
+        let mut body = json!({
+            "reason": reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        if let Some(text) = text {
+            body["description"] = json!(text);
+            body["text"] = json!(text);
+        }
+        self.event("stopped", body);
+    }
+
+    fn line_of_pos(&self, pos: usize) -> usize {
+        // The last listed instruction starting at or before `pos', self-modified code may not line up
+        match self.listing.binary_search_by_key(&pos, |(addr, _)| *addr) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx.max(1),
+        }
+    }
+
+    // The first decoded instruction at or after `pos', where a breakpoint there can be hit
+    fn instruction_from(&self, pos: usize) -> Option<usize> {
+        self.analysis
+            .as_ref()?
+            .instructions
+            .range(pos..)
+            .next()
+            .map(|(addr, _)| *addr)
+    }
+
+    fn update_breakpoints(&mut self) {
+        let all = self
+            .line_breakpoints
+            .union(&self.instruction_breakpoints)
+            .copied()
+            .collect();
+        self.m.set_breakpoints(all);
+    }
+
+    fn report_outputs(&mut self) {
+        let new: Vec<isize> = self
+            .m
+            .outputs()
+            .iter()
+            .skip(self.reported_outputs)
+            .copied()
+            .collect();
+        self.reported_outputs += new.len();
+        for out in new {
+            self.event(
+                "output",
+                json!({ "category": "stdout", "output": format!("OUTPUT : {}\n", out) }),
+            );
+        }
+    }
+
+    // Runs one instruction, or the next slice of a `continue'
+    fn resume(&mut self, single_step: bool) {
+        let budget = if single_step { 1 } else { RUN_SLICE };
+        self.m.set_step_limit(Some(self.m.steps() + budget));
+        let result = run_machine(&mut self.m);
+        self.report_outputs();
+        self.running = false;
+        match result {
+            RunResult::StepLimit if !single_step => self.running = true,
+            RunResult::StepLimit => self.stopped("step", None),
+            RunResult::Breakpoint => self.stopped("breakpoint", None),
+            RunResult::WaitingForInput => self.stopped(
+                "pause",
+                Some("Waiting for input, queue more with `input <value>'".to_string()),
+            ),
+            RunResult::IllegalOperation => {
+                self.stopped("exception", Some("Illegal operation".to_string()))
+            }
+            RunResult::EndOfMemory => self.stopped(
+                "exception",
+                Some("Ran past the end of the program".to_string()),
+            ),
+            RunResult::NotLoaded => {
+                self.stopped("exception", Some("No program is loaded".to_string()))
+            }
+            RunResult::DeadlinePassed => {
+                self.stopped("exception", Some("Deadline passed".to_string()))
+            }
+            RunResult::Loop(info) => self.stopped(
+                "exception",
+                Some(format!(
+                    "Stuck in a loop of {} instructions, closed by the jump at {}",
+                    info.length, info.pos
+                )),
+            ),
+            RunResult::Halted => {
+                self.event("exited", json!({ "exitCode": 0 }));
+                self.event("terminated", json!({}));
+            }
+        }
+    }
+
+    fn launch(&mut self, args: &Value) -> Result<Value, String> {
+        let program = args["program"]
+            .as_str()
+            .ok_or("Missing `program' in launch arguments")?;
+        self.m = load_program(program)?;
+        self.m.set_terminal(false);
+        self.m.set_interactive(false);
+        self.m.set_loop_detection(true); // Stops a stuck program that loops within one slice
+        if let Some(inputs) = args["inputs"].as_array() {
+            for input in inputs {
+                let input = input
+                    .as_i64()
+                    .ok_or("`inputs' must be a list of integers")?;
+                self.m.put_input(input as isize);
+            }
+        }
+        self.program = program.to_string();
+        let analysis = analyse(&self.m);
+        self.m
+            .set_decode_cache(analysis.instructions.keys().copied());
+        self.listing = disassemble_reachable(&self.m, &analysis);
+        self.analysis = Some(analysis);
+        self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+        self.event("initialized", json!({}));
+        Ok(json!({}))
+    }
+
+    fn set_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        let mut breakpoints = Vec::new();
+        self.line_breakpoints.clear();
+        for bp in args["breakpoints"].as_array().unwrap_or(&Vec::new()) {
+            let line = bp["line"].as_u64().unwrap_or(0) as usize;
+            let addr = self
+                .listing
+                .get(line.wrapping_sub(1))
+                .and_then(|(addr, _)| self.instruction_from(*addr));
+            match addr {
+                Some(addr) => {
+                    // A data line moves down to the next instruction
+                    self.line_breakpoints.insert(addr);
+                    breakpoints.push(json!({
+                        "verified": true,
+                        "line": self.line_of_pos(addr),
+                        "instructionReference": addr.to_string(),
+                    }));
+                }
+                None => breakpoints.push(json!({
+                    "verified": false,
+                    "line": line,
+                    "message": "No instruction on this line",
+                })),
+            }
+        }
+        self.update_breakpoints();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn set_instruction_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        let mut breakpoints = Vec::new();
+        self.instruction_breakpoints.clear();
+        for bp in args["breakpoints"].as_array().unwrap_or(&Vec::new()) {
+            let addr = bp["instructionReference"]
+                .as_str()
+                .and_then(parse_address)
+                .map(|addr| addr as i64 + bp["offset"].as_i64().unwrap_or(0));
+            let landed = match addr {
+                Some(addr) if addr >= 0 && (addr as usize) < self.m.len() => self
+                    .instruction_from(addr as usize)
+                    .map(|landed| (addr, landed)),
+                _ => None,
+            };
+            match landed {
+                Some((addr, landed)) => {
+                    // An address inside an instruction or in data moves to the next instruction
+                    self.instruction_breakpoints.insert(landed);
+                    let mut breakpoint = json!({
+                        "verified": true,
+                        "instructionReference": landed.to_string(),
+                        "line": self.line_of_pos(landed),
+                    });
+                    if landed as i64 != addr {
+                        breakpoint["message"] = json!(format!(
+                            "{} does not start an instruction, moved to {}",
+                            addr, landed
+                        ));
+                    }
+                    breakpoints.push(breakpoint);
+                }
+                None => breakpoints.push(json!({
+                    "verified": false,
+                    "message": "No instruction at or after this address in the program",
+                })),
+            }
+        }
+        self.update_breakpoints();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn source(&self) -> Value {
+        let name = std::path::Path::new(&self.program)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        json!({ "name": format!("{} (disassembly)", name), "sourceReference": SOURCE_REFERENCE })
+    }
+
+    fn stack_trace(&self) -> Value {
+        let frame = json!({
+            "id": 1,
+            "name": machine_op_at_to_string(&self.m, self.m.pos).0,
+            "source": self.source(),
+            "line": self.line_of_pos(self.m.pos),
+            "column": 1,
+            "instructionPointerReference": self.m.pos.to_string(),
+        });
+        json!({ "stackFrames": [frame], "totalFrames": 1 })
+    }
+
+    fn source_content(&self) -> Value {
+        let mut content = String::new();
+        for (addr, text) in &self.listing {
+            content += &format!("{:>6}: {}\n", addr, text);
+        }
+        json!({ "content": content, "mimeType": "text/plain" })
+    }
+
+    fn disassemble(&self, args: &Value) -> Result<Value, String> {
+        let base = args["memoryReference"]
+            .as_str()
+            .and_then(parse_address)
+            .ok_or("Invalid memory reference")? as i64
+            + args["offset"].as_i64().unwrap_or(0);
+        let count = args["instructionCount"].as_i64().unwrap_or(0);
+        let start = match self
+            .listing
+            .binary_search_by_key(&base, |(addr, _)| *addr as i64)
+        {
+            Ok(idx) => idx as i64,
+            Err(idx) => idx as i64 - 1,
+        } + args["instructionOffset"].as_i64().unwrap_or(0);
+
+        let mut instructions = Vec::new();
+        for idx in start..start + count {
+            let instruction = if idx >= 0 && (idx as usize) < self.listing.len() {
+                let addr = self.listing[idx as usize].0;
+                json!({
+                    "address": addr.to_string(),
+                    "instruction": machine_op_at_to_string(&self.m, addr).0,
+                    "location": self.source(),
+                    "line": idx + 1,
+                })
+            } else {
+                // The client asks for a fixed count around the pc, pad with placeholders
+                json!({
+                    "address": (idx.max(-1) + 1).to_string(),
+                    "instruction": "",
+                    "presentationHint": "invalid",
+                })
+            };
+            instructions.push(instruction);
+        }
+        Ok(json!({ "instructions": instructions }))
+    }
+
+    fn variables(&self, reference: u64) -> Value {
+        let var = |name: String, value: String, reference: u64| json!({ "name": name, "value": value, "variablesReference": reference });
+        let mut variables = Vec::new();
+        if reference == REGISTERS_REFERENCE {
+            variables.push(var("pc".to_string(), self.m.pos.to_string(), 0));
+            variables.push(var(
+                "relative base".to_string(),
+                self.m.relative_base.to_string(),
+                0,
+            ));
+            variables.push(var("steps".to_string(), self.m.steps().to_string(), 0));
+            variables.push(var(
+                "inputs".to_string(),
+                format!("{:?}", self.m.inputs()),
+                0,
+            ));
+            variables.push(var(
+                "outputs".to_string(),
+                format!("{:?}", self.m.outputs()),
+                0,
+            ));
+        } else if reference == MEMORY_REFERENCE {
+            let used = self.m.mem_used().max(self.m.pos + 1);
+            for row in 0..used.div_ceil(MEMORY_ROW_WIDTH) {
+                let start = row * MEMORY_ROW_WIDTH;
+                let cells: Vec<String> = self.m.mem[start..start + MEMORY_ROW_WIDTH]
+                    .iter()
+                    .map(|cell| cell.to_string())
+                    .collect();
+                variables.push(var(
+                    start.to_string(),
+                    cells.join(" "),
+                    MEMORY_ROW_REFERENCE_BASE + row as u64,
+                ));
+            }
+        } else if reference >= MEMORY_ROW_REFERENCE_BASE {
+            let start = (reference - MEMORY_ROW_REFERENCE_BASE) as usize * MEMORY_ROW_WIDTH;
+            for addr in start..(start + MEMORY_ROW_WIDTH).min(self.m.mem.len()) {
+                let marker = if addr == self.m.pos { " <" } else { "" };
+                let mut variable = var(
+                    format!("[{}]", addr),
+                    format!("{}{}", self.m.mem[addr], marker),
+                    0,
+                );
+                if let Some(analysis) = &self.analysis {
+                    variable["type"] = json!(analysis.class_of(addr).name());
+                }
+                variables.push(variable);
+            }
+        }
+        json!({ "variables": variables })
+    }
+
+    fn evaluate(&mut self, args: &Value) -> Result<Value, String> {
+        let expression = args["expression"].as_str().unwrap_or("").trim();
+        let result = if let Some(inputs) = expression.strip_prefix("input") {
+            let mut count = 0;
+            for input in inputs.split(|c: char| c == ',' || c.is_whitespace()) {
+                if input.is_empty() {
+                    continue;
+                }
+                let input = input
+                    .parse::<isize>()
+                    .map_err(|_| format!("Unable to parse input `{}'", input))?;
+                self.m.put_input(input);
+                count += 1;
+            }
+            format!("Queued {} inputs, {:?}", count, self.m.inputs())
+        } else if expression == "pc" {
+            self.m.pos.to_string()
+        } else if expression == "rb" {
+            self.m.relative_base.to_string()
+        } else {
+            let addr = expression
+                .strip_prefix("mem[")
+                .and_then(|s| s.strip_suffix(']'))
+                .unwrap_or(expression);
+            let addr = parse_address(addr).ok_or(format!("Unknown expression `{}'", expression))?;
+            self.m
+                .mem
+                .get(addr)
+                .ok_or(format!("Address {} is out of memory", addr))?
+                .to_string()
+        };
+        Ok(json!({ "result": result, "variablesReference": 0 }))
+    }
+
+    // Returns false when the session is over
+    fn handle(&mut self, request: &Value) -> bool {
+        let command = request["command"].as_str().unwrap_or("");
+        let args = &request["arguments"];
+        let mut running = true;
+        let result: Result<Value, String> = match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsInstructionBreakpoints": true,
+                "supportsDisassembleRequest": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" => self.launch(args),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setInstructionBreakpoints" => self.set_instruction_breakpoints(args),
+            "setExceptionBreakpoints" => Ok(json!({})),
+            "configurationDone" => {
+                if self.stop_on_entry {
+                    self.stopped("entry", None);
+                } else {
+                    self.running = true;
+                }
+                Ok(json!({}))
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "intcode" }] })),
+            "stackTrace" => Ok(self.stack_trace()),
+            "source" => Ok(self.source_content()),
+            "disassemble" => self.disassemble(args),
+            "scopes" => Ok(json!({ "scopes": [
+                { "name": "Registers", "variablesReference": REGISTERS_REFERENCE, "expensive": false },
+                { "name": "Memory", "variablesReference": MEMORY_REFERENCE, "expensive": true },
+            ]})),
+            "variables" => Ok(self.variables(args["variablesReference"].as_u64().unwrap_or(0))),
+            "evaluate" => self.evaluate(args),
+            "continue" => {
+                self.running = true;
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" | "stepIn" | "stepOut" => {
+                self.resume(true);
+                Ok(json!({}))
+            }
+            "pause" => {
+                // Between slices of a `continue', a stopped machine is already paused
+                if self.running {
+                    self.running = false;
+                    self.stopped("pause", None);
+                }
+                Ok(json!({}))
+            }
+            "disconnect" | "terminate" => {
+                running = false;
+                Ok(json!({}))
+            }
+            _ => Err(format!("Unsupported request `{}'", command)),
+        };
+
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        self.send(response);
+        self.send_events();
+        running
+    }
+
+    fn send_events(&mut self) {
+        for (event, body) in std::mem::take(&mut self.events) {
+            self.send(json!({ "type": "event", "event": event, "body": body }));
+        }
+    }
+}
+
+fn main() {
+    // Requests are read on their own thread so they can arrive while the machine runs
+    let (sender, requests) = channel();
+    std::thread::spawn(move || {
+        let stdin = std::io::stdin();
+        let mut input = stdin.lock();
+        while let Some(request) = read_message(&mut input) {
+            if sender.send(request).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut adapter = Adapter::new();
+    loop {
+        if adapter.running {
+            adapter.resume(false);
+            adapter.send_events();
+        }
+        let request = if adapter.running {
+            match requests.try_recv() {
+                Ok(request) => request,
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        } else {
+            match requests.recv() {
+                Ok(request) => request,
+                Err(_) => break,
+            }
+        };
+        if request["type"] == "request" && !adapter.handle(&request) {
+            break;
+        }
+    }
+}
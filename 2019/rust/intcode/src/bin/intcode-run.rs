@@ -0,0 +1,30 @@
+// Runs an Intcode program with `run_machine', inputs from the command line
+//
+// Usage: intcode-run <program> [inputs...]
+//
+// Prints every output, then how the run ended and the final value at position 0.
+
+use intcode::analysis::*;
+use intcode::machine::*;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <program> [inputs...]", args[0]);
+        std::process::exit(1);
+    }
+    let mut m = Machine::new();
+    load_machine_from_file(&mut m, &args[1]);
+    m.set_terminal(false);
+    m.set_interactive(false);
+    m.set_decode_cache(analyse(&m).instructions.keys().copied());
+    for input in &args[2..] {
+        m.put_input(input.parse().expect("Unable to parse input"));
+    }
+    let result = run_machine(&mut m);
+    while let Some(output) = m.get_output() {
+        println!("{}", output);
+    }
+    println!("exit: {:?}", result);
+    println!("mem[0]: {}", m.mem[0]);
+}
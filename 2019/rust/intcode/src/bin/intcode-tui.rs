@@ -0,0 +1,388 @@
+// Full screen terminal debugger for Intcode programs
+//
+// Usage: intcode-tui <program> [inputs...]
+//
+// The screen is redrawn in place after every command. Commands:
+//   <enter>, s [n]   step one or n instructions
+//   c                continue until a breakpoint, halt, loop or missing input, enter pauses
+//   b <addr>         toggle a breakpoint
+//   i <values...>    queue input values
+//   m <addr>         show the memory row holding <addr>
+//   hex, dec         memory grid radix
+//   r                read the terminal size again and redraw
+//   q                quit
+
+use intcode::analysis::*;
+use intcode::machine::*;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, TryRecvError};
+use std::time::{Duration, Instant};
+
+const CL_WRITE: &str = "\x1B[34m"; // Cells written by the last instruction
+const CL_PC: &str = "\x1B[7m"; // The cell at pc
+const CL_FG: &str = "\x1B[0m";
+const CL_READ_ONLY: &str = "\x1B[36m"; // Data the program only reads
+const CL_READ_WRITE: &str = "\x1B[33m"; // Data the program writes
+const CL_UNREACHABLE: &str = "\x1B[2m"; // Cells no known instruction touches
+const ALT_SCREEN_ON: &str = "\x1B[?1049h";
+const ALT_SCREEN_OFF: &str = "\x1B[?1049l";
+const CURSOR_HOME: &str = "\x1B[H";
+const CLEAR_LINE: &str = "\x1B[K"; // Clear the rest of the line
+const CLEAR_BELOW: &str = "\x1B[J"; // Clear the rest of the screen
+
+const DISASM_BEFORE: usize = 6; // Instructions shown before pc
+const DISASM_AFTER: usize = 10; // Instructions shown after pc, including pc
+const DISASM_WIDTH: usize = 50;
+const MEM_ROW_WIDTH: usize = 10;
+const RUN_SLICE: Duration = Duration::from_millis(100); // Time run by `c' between redraws
+
+struct Tui {
+    m: Machine,
+    program: String,
+    listing: Vec<(usize, String)>,
+    analysis: Analysis,
+    breakpoints: BTreeSet<usize>,
+    hex: bool,
+    mem_focus: Option<usize>, // Extra memory row to show, set by `m <addr>'
+    message: String,          // Shown above the command line
+    size: (usize, usize),     // Columns and rows of the terminal, read at start and by `r'
+    running: bool,            // A `c' is in progress, run a slice at a time
+}
+
+fn terminal_size() -> (usize, usize) {
+    // Columns and rows, `stty' reads them from the terminal on stdin
+    Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()
+        .and_then(|out| {
+            let out = String::from_utf8(out.stdout).ok()?;
+            let mut split = out.split_whitespace();
+            let rows = split.next()?.parse().ok()?;
+            let cols = split.next()?.parse().ok()?;
+            Some((cols, rows))
+        })
+        .unwrap_or((120, 40))
+}
+
+fn pad(s: &str, width: usize) -> String {
+    let mut s: String = s.chars().take(width).collect();
+    while s.chars().count() < width {
+        s.push(' ');
+    }
+    s
+}
+
+impl Tui {
+    fn disassembly(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        // Instructions before pc come from the static listing, pc and onwards are decoded live
+        let before = self
+            .listing
+            .iter()
+            .filter(|(addr, _)| *addr < self.m.pos)
+            .collect::<Vec<_>>();
+        let mut lines: Vec<(usize, String)> = before
+            .iter()
+            .skip(before.len().saturating_sub(DISASM_BEFORE))
+            .map(|(addr, _)| (*addr, machine_op_at_to_string(&self.m, *addr).0))
+            .collect();
+        let mut pos = self.m.pos;
+        for _ in 0..DISASM_AFTER {
+            if pos >= self.m.mem.len() {
+                break;
+            }
+            let (text, width) = machine_op_at_to_string(&self.m, pos);
+            lines.push((pos, text));
+            pos += width;
+        }
+        for (addr, text) in lines {
+            let marker = if addr == self.m.pos { '>' } else { ' ' };
+            let bp = if self.breakpoints.contains(&addr) {
+                '*'
+            } else {
+                ' '
+            };
+            out.push(format!("{}{}{:>6}: {}", bp, marker, addr, text));
+        }
+        out
+    }
+
+    fn registers(&self) -> Vec<String> {
+        let queue = |q: &std::collections::VecDeque<isize>| {
+            q.iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        vec![
+            format!("pc            {}", self.m.pos),
+            format!("relative base {}", self.m.relative_base),
+            format!("steps         {}", self.m.steps()),
+            format!("inputs        [{}]", queue(self.m.inputs())),
+            format!("outputs       [{}]", queue(self.m.outputs())),
+            format!("last writes   {:?}", self.m.last_writes()),
+        ]
+    }
+
+    fn mem_grid(&self, max_rows: usize) -> Vec<String> {
+        // Same row selection as `machine_mem_to_string': the row of pc, plus rows with highlights
+        let mut rows: BTreeSet<usize> = BTreeSet::new();
+        rows.insert(self.m.pos / MEM_ROW_WIDTH * MEM_ROW_WIDTH);
+        for pos in self.m.last_writes() {
+            rows.insert(pos / MEM_ROW_WIDTH * MEM_ROW_WIDTH);
+        }
+        if let Some(focus) = self.mem_focus {
+            rows.insert(focus / MEM_ROW_WIDTH * MEM_ROW_WIDTH);
+        }
+        // Fill up with the rows following pc
+        let mut next = self.m.pos / MEM_ROW_WIDTH * MEM_ROW_WIDTH;
+        while rows.len() < max_rows && next + MEM_ROW_WIDTH <= self.m.mem.len() {
+            rows.insert(next);
+            next += MEM_ROW_WIDTH;
+        }
+
+        let mut out = Vec::new();
+        out.push(format!(
+            "{:>7} code, {}read-only{}, {}read-write{}, {}unreachable{}",
+            "", CL_READ_ONLY, CL_FG, CL_READ_WRITE, CL_FG, CL_UNREACHABLE, CL_FG
+        ));
+        let mut header = format!("{:>7}+", "");
+        for idx in 0..MEM_ROW_WIDTH {
+            header += &format!(" {:>11}", idx);
+        }
+        out.push(header);
+        for row in rows.into_iter().take(max_rows) {
+            let mut line = format!("{:>7}:", row);
+            for pos in row..row + MEM_ROW_WIDTH {
+                let value = self.m.mem[pos];
+                let text = if self.hex {
+                    if value < 0 {
+                        format!("-{:x}", -(value as i128))
+                    } else {
+                        format!("{:x}", value)
+                    }
+                } else {
+                    value.to_string()
+                };
+                let color = if pos == self.m.pos {
+                    CL_PC
+                } else if self.m.last_writes().contains(&pos) {
+                    CL_WRITE
+                } else {
+                    match self.analysis.class_of(pos) {
+                        CellClass::Code => "",
+                        CellClass::ReadOnlyData => CL_READ_ONLY,
+                        CellClass::ReadWriteData => CL_READ_WRITE,
+                        CellClass::Unreachable => CL_UNREACHABLE,
+                    }
+                };
+                let reset = if color.is_empty() { "" } else { CL_FG };
+                line += &format!(" {}{:>11}{}", color, text, reset);
+            }
+            out.push(line);
+        }
+        out
+    }
+
+    fn draw(&self) {
+        let (cols, rows) = self.size;
+        let mut screen: Vec<String> = Vec::new();
+        screen.push(format!(
+            "intcode-tui  {}  ({} ints{})",
+            self.program,
+            self.m.len(),
+            if self.analysis.is_self_modifying() {
+                ", self-modifying"
+            } else {
+                ""
+            }
+        ));
+        screen.push("-".repeat(cols));
+
+        let left = self.disassembly();
+        let right = self.registers();
+        for idx in 0..left.len().max(right.len()) {
+            let l = left.get(idx).map(String::as_str).unwrap_or("");
+            let r = right.get(idx).map(String::as_str).unwrap_or("");
+            screen.push(format!("{} | {}", pad(l, DISASM_WIDTH), r));
+        }
+        screen.push("-".repeat(cols));
+
+        // Whatever is left goes to the memory grid, keep room for the message and command line
+        let mem_rows = rows.saturating_sub(screen.len() + 5).max(1);
+        screen.extend(self.mem_grid(mem_rows));
+        screen.push("-".repeat(cols));
+        screen.push(self.message.clone());
+
+        let mut out = String::new();
+        out += CURSOR_HOME;
+        for line in &screen {
+            out += line;
+            out += CLEAR_LINE;
+            out += "\n";
+        }
+        out += CLEAR_BELOW;
+        out += "> ";
+        print!("{}", out);
+        std::io::stdout().flush().ok();
+    }
+
+    // Runs `steps' instructions, or until `deadline' when continuing
+    fn run(&mut self, steps: Option<usize>, deadline: Option<Instant>) -> RunResult {
+        self.m
+            .set_step_limit(steps.map(|steps| self.m.steps() + steps));
+        self.m.set_deadline(deadline);
+        let result = run_machine(&mut self.m);
+        self.message = match result {
+            RunResult::Halted => "Machine halted".to_string(),
+            RunResult::WaitingForInput => {
+                "Waiting for input, queue more with `i <values>'".to_string()
+            }
+            RunResult::EndOfMemory => "Machine reached end of memory".to_string(),
+            RunResult::IllegalOperation => "Illegal operation".to_string(),
+            RunResult::NotLoaded => "Machine not loaded".to_string(),
+            RunResult::Breakpoint => format!("Breakpoint at {}", self.m.pos),
+            RunResult::StepLimit => String::new(),
+            RunResult::DeadlinePassed => {
+                format!("Running, {} steps, press enter to pause", self.m.steps())
+            }
+            RunResult::Loop(info) => format!(
+                "Stuck in a loop of {} instructions, closed by the jump at {}",
+                info.length, info.pos
+            ),
+        };
+        result
+    }
+
+    // The next slice of a `c', loop detection only sees repeats within one slice
+    fn run_slice(&mut self) {
+        let result = self.run(None, Some(Instant::now() + RUN_SLICE));
+        self.running = result == RunResult::DeadlinePassed;
+    }
+
+    // Returns false on quit
+    fn command(&mut self, line: &str) -> bool {
+        let mut split = line.split_whitespace();
+        let cmd = split.next().unwrap_or("s");
+        let args: Vec<&str> = split.collect();
+        let parse_arg = |idx: usize| args.get(idx).and_then(|a| a.parse::<usize>().ok());
+        match cmd {
+            "s" | "step" => {
+                self.run(Some(parse_arg(0).unwrap_or(1)), None);
+            }
+            "c" | "continue" => self.running = true,
+            "b" | "break" => match parse_arg(0) {
+                Some(addr) => {
+                    if !self.breakpoints.remove(&addr) {
+                        self.breakpoints.insert(addr);
+                    }
+                    self.m.set_breakpoints(self.breakpoints.clone());
+                    self.message = format!("Breakpoints: {:?}", self.breakpoints);
+                }
+                None => self.message = "Usage: b <addr>".to_string(),
+            },
+            "i" | "input" => {
+                let values: Result<Vec<isize>, _> = args.iter().map(|a| a.parse()).collect();
+                match values {
+                    Ok(values) => {
+                        for value in values {
+                            self.m.put_input(value);
+                        }
+                        self.message = format!("Inputs: {:?}", self.m.inputs());
+                    }
+                    Err(_) => self.message = "Usage: i <values...>".to_string(),
+                }
+            }
+            "m" | "mem" => match parse_arg(0) {
+                Some(addr) if addr < self.m.mem.len() => {
+                    self.mem_focus = Some(addr);
+                    self.message = String::new();
+                }
+                _ => self.message = "Usage: m <addr>".to_string(),
+            },
+            "hex" => self.hex = true,
+            "dec" => self.hex = false,
+            "r" | "refresh" => {
+                self.size = terminal_size();
+                self.message = String::new();
+            }
+            "q" | "quit" => return false,
+            _ => self.message = format!("Unknown command `{}'", cmd),
+        }
+        true
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <program> [inputs...]", args[0]);
+        std::process::exit(1);
+    }
+    let mut m = Machine::new();
+    load_machine_from_file(&mut m, &args[1]);
+    m.set_terminal(false);
+    m.set_interactive(false);
+    m.set_loop_detection(true);
+    let analysis = analyse(&m);
+    m.set_decode_cache(analysis.instructions.keys().copied());
+    for input in &args[2..] {
+        m.put_input(input.parse().expect("Unable to parse input"));
+    }
+
+    let mut tui = Tui {
+        listing: disassemble_reachable(&m, &analysis),
+        analysis,
+        m,
+        program: args[1].clone(),
+        breakpoints: BTreeSet::new(),
+        hex: false,
+        mem_focus: None,
+        message: "Press enter to step, `c' to continue, `q' to quit".to_string(),
+        size: terminal_size(),
+        running: false,
+    };
+
+    // Lines are read on their own thread so a running `c' can be paused
+    let (sender, lines) = channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    print!("{}", ALT_SCREEN_ON);
+    loop {
+        if tui.running {
+            tui.run_slice();
+        }
+        tui.draw();
+        let line = if tui.running {
+            match lines.try_recv() {
+                Ok(_) => {
+                    tui.running = false;
+                    tui.message = format!("Paused at {}", tui.m.pos);
+                    continue;
+                }
+                Err(TryRecvError::Empty) => continue,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        } else {
+            match lines.recv() {
+                Ok(line) => line,
+                Err(_) => break,
+            }
+        };
+        if !tui.command(line.trim()) {
+            break;
+        }
+    }
+    print!("{}", ALT_SCREEN_OFF);
+    std::io::stdout().flush().ok();
+}
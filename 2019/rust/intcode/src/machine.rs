@@ -0,0 +1,971 @@
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::io::{BufRead, BufReader};
+use std::time::Instant;
+
+pub const PRINT_DEBUG: bool = false;
+
+const MEM_LENGTH: usize = 10000;
+const CL_RED: &str = "\x1B[34m";
+const CL_FG: &str = "\x1B[0m";
+const DEADLINE_CHECK_INTERVAL: usize = 1024; // Steps between each look at the clock
+
+pub const OPS: [OpInfo; 10] = [
+    OpInfo {
+        name: "ADD",
+        id: 1,
+        n_params: 3,
+        _n_inouts: 0,
+        func: op_add,
+    }, // Addition
+    OpInfo {
+        name: "MULT",
+        id: 2,
+        n_params: 3,
+        _n_inouts: 0,
+        func: op_mult,
+    }, // Multiplication
+    OpInfo {
+        name: "IN",
+        id: 3,
+        n_params: 1,
+        _n_inouts: 1,
+        func: op_in,
+    }, // Input
+    OpInfo {
+        name: "OUT",
+        id: 4,
+        n_params: 1,
+        _n_inouts: 1,
+        func: op_out,
+    }, // Output - print to screen
+    OpInfo {
+        name: "JIT",
+        id: 5,
+        n_params: 2,
+        _n_inouts: 0,
+        func: op_jit,
+    }, // Jump-if-true
+    OpInfo {
+        name: "JIF",
+        id: 6,
+        n_params: 2,
+        _n_inouts: 0,
+        func: op_jif,
+    }, // Jump-if-false
+    OpInfo {
+        name: "LESS",
+        id: 7,
+        n_params: 3,
+        _n_inouts: 0,
+        func: op_less,
+    }, // Less than
+    OpInfo {
+        name: "EQ",
+        id: 8,
+        n_params: 3,
+        _n_inouts: 0,
+        func: op_eq,
+    }, // Equals
+    OpInfo {
+        name: "RBASE",
+        id: 9,
+        n_params: 1,
+        _n_inouts: 0,
+        func: op_rbase,
+    }, // Change relative base
+    OpInfo {
+        name: "HALT",
+        id: 99,
+        n_params: 0,
+        _n_inouts: 0,
+        func: op_halt,
+    }, // End program
+];
+
+#[derive(Clone)]
+pub struct Machine {
+    pub pos: usize,
+    pub mem: [isize; MEM_LENGTH],
+    len: usize,
+    halted: bool,
+    outputs: VecDeque<isize>,
+    inputs: VecDeque<isize>,
+    terminal: bool,
+    interactive: bool,
+    pub relative_base: isize,
+    steps: usize,                  // Number of instructions executed so far
+    step_limit: Option<usize>,     // Stop once this many instructions have been executed
+    deadline: Option<Instant>,     // Stop once the wall clock passes this point
+    loop_detection: bool,          // Stop when the machine state repeats at a backward jump
+    high_water: usize,             // Everything at and above this address is still zero
+    breakpoints: BTreeSet<usize>,  // Stop before executing an instruction at any of these positions
+    stopped_at: Option<usize>,     // Where the last run stopped, resuming skips its breakpoint
+    last_writes: Vec<usize>,       // Positions written by the last executed instruction
+    decode_cache: Vec<Option<Op>>, // Operations decoded ahead of time, dropped when written to
+}
+
+// Why `run_machine' returned
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RunResult {
+    Halted,          // Reached a HALT instruction
+    WaitingForInput, // Needs more input, call `run_machine' again after `put_input'
+    EndOfMemory,     // Ran past the loaded program
+    IllegalOperation,
+    NotLoaded,
+    Breakpoint,     // Stopped before the instruction at a breakpoint
+    StepLimit,      // The instruction budget is spent
+    DeadlinePassed, // The wall clock deadline has passed
+    Loop(LoopInfo), // The machine will never halt nor ask for input
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LoopInfo {
+    pub pos: usize,    // The position of the backward jump that closes the cycle
+    pub start: usize,  // Step count when the repeated state was first seen
+    pub length: usize, // Number of instructions in one turn of the cycle
+}
+
+// A full copy of the machine state, used to confirm a loop when two hashes match
+struct Snapshot {
+    step: usize,
+    pos: usize,
+    relative_base: isize,
+    mem: Vec<isize>,
+}
+
+impl Machine {
+    pub fn new() -> Self {
+        Machine {
+            pos: 0,
+            mem: [0; MEM_LENGTH],
+            len: 0,
+            halted: true,
+            outputs: VecDeque::new(),
+            inputs: VecDeque::new(),
+            terminal: false, // Connect the output to the terminal
+            interactive: false,
+            relative_base: 0,
+            steps: 0,
+            step_limit: None,
+            deadline: None,
+            loop_detection: false,
+            high_water: 0,
+            breakpoints: BTreeSet::new(),
+            stopped_at: None,
+            last_writes: Vec::new(),
+            decode_cache: Vec::new(),
+        }
+    }
+    pub fn put_input(&mut self, input: isize) {
+        self.inputs.push_back(input);
+    }
+    pub fn get_output(&mut self) -> Option<isize> {
+        self.outputs.pop_front()
+    }
+    pub fn set_terminal(&mut self, b: bool) {
+        self.terminal = b;
+    }
+    pub fn set_interactive(&mut self, b: bool) {
+        self.interactive = b;
+    }
+    pub fn set_step_limit(&mut self, limit: Option<usize>) {
+        self.step_limit = limit;
+    }
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+    pub fn set_loop_detection(&mut self, b: bool) {
+        self.loop_detection = b;
+    }
+    pub fn set_breakpoints(&mut self, breakpoints: BTreeSet<usize>) {
+        self.breakpoints = breakpoints;
+    }
+    // Decodes the operations at `positions' once, typically the code found by `analysis::analyse'
+    // Writes by the program drop the cached entry, call this again after patching `mem' directly
+    pub fn set_decode_cache(&mut self, positions: impl IntoIterator<Item = usize>) {
+        self.decode_cache.clear();
+        for pos in positions {
+            if pos >= self.decode_cache.len() {
+                self.decode_cache.resize_with(pos + 1, || None);
+            }
+            self.decode_cache[pos] = split_opcode(self.mem[pos]);
+        }
+    }
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn last_writes(&self) -> &Vec<usize> {
+        &self.last_writes
+    }
+    pub fn mem_used(&self) -> usize {
+        self.high_water
+    }
+    pub fn inputs(&self) -> &VecDeque<isize> {
+        &self.inputs
+    }
+    pub fn outputs(&self) -> &VecDeque<isize> {
+        &self.outputs
+    }
+    pub fn memcpy(&mut self, other: &Machine) {
+        self.mem = other.mem;
+        self.len = other.len;
+        self.high_water = other.high_water;
+        self.decode_cache = other.decode_cache.clone();
+    }
+    pub fn is_halted(&self) -> bool {
+        self.halted && (self.mem[self.pos] == opinfo_from_name("HALT").unwrap().id)
+    }
+    pub fn reset(&mut self) {
+        self.pos = 0;
+        self.stopped_at = None;
+    }
+    pub fn output_waiting(&mut self) -> usize {
+        self.outputs.len()
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine::new()
+    }
+}
+
+#[derive(Clone)]
+pub struct Op {
+    pub id: isize,
+    pub param_modes: Vec<ParamMode>,
+}
+
+pub struct OpInfo<'a> {
+    pub name: &'a str,    // Name
+    pub id: isize,        // ID
+    pub n_params: usize,  // Number of parameters
+    pub _n_inouts: usize, // Number of inouts
+    pub func: fn(
+        &mut Machine,
+        Option<&mut Vec<usize>>, // Optional list of positions to print with color to terminal
+    ) -> bool, // Returns whether to automatically increase the machine position
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ParamMode {
+    Indirect, // aka. Position Mode
+    Immediate,
+    Relative,
+}
+
+pub fn to_param_mode(a: usize) -> Option<ParamMode> {
+    match a {
+        0 => Some(ParamMode::Indirect),
+        1 => Some(ParamMode::Immediate),
+        2 => Some(ParamMode::Relative),
+        _ => None,
+    }
+}
+
+pub fn parammode_to_string(mode: ParamMode) -> String {
+    match mode {
+        ParamMode::Indirect => "*".to_string(),
+        ParamMode::Immediate => "#".to_string(),
+        ParamMode::Relative => "~".to_string(),
+    }
+}
+
+pub fn machine_pos_and_op_to_string(m: &Machine, // Machine to print
+) -> String {
+    let mut out: String = "".to_string();
+    out += &format!("{:>6}Current pos = {}(/{}):\n", "", m.pos, m.len - 1);
+    let op = split_opcode(m.mem[m.pos]);
+    match op {
+        None => {
+            out += &format!("Unkown operation: {}\n", m.mem[m.pos]);
+        }
+        Some(op) => {
+            let opinfo = opinfo_from_id(op.id).unwrap();
+            out += &format!("{:>6}{} ({}) -> ", "", op.id, opinfo.name);
+            for (idx, mode) in op.param_modes.iter().enumerate() {
+                out += &parammode_to_string(*mode);
+                out += &format!("{} ", m.mem[m.pos + idx + 1]);
+            }
+            out += "| ";
+            for (idx, mode) in op.param_modes.iter().enumerate() {
+                out += &format!("{} ", m.mem[unroll_parammode(m, m.pos + idx + 1, *mode)]);
+            }
+        }
+    }
+    out
+}
+
+pub fn machine_mem_to_string(
+    m: &Machine,                     // Machine to print
+    v: Option<&Vec<usize>>,          // Vector of adresses to highlight
+    range: Option<&BTreeSet<usize>>, // Positions to print, if `None', defaults to only the machine's current position
+    radix: Option<usize>,            // The width of the print
+) -> String {
+    // if PRINT_DEBUG {println!("v is {:?}", v)}
+    // Some input validation
+    let new_vec = Vec::<usize>::new();
+    let v: &Vec<usize> = v.unwrap_or(&new_vec);
+    // if PRINT_DEBUG {println!("v is {:?}", v)}
+    let radix = match radix {
+        None | Some(0) => 10,
+        Some(radix) => radix,
+    };
+
+    fn get_is_highlighted(pos: usize, v: &[usize]) -> (&'static str, &'static str) {
+        // if PRINT_DEBUG {println!("HL: @{} <= {:?}", pos, v)}
+        if v.contains(&pos) {
+            (CL_RED, CL_FG)
+        } else {
+            ("", "")
+        }
+    }
+    let mut out: String = "".to_string();
+    let mut rows: BTreeSet<usize> = BTreeSet::new();
+    // We default to only the machine's current position
+    rows.insert(m.pos / radix * radix); // Round down to nearest radix
+    if let Some(range) = range {
+        for pos in range {
+            rows.insert(pos / radix * radix); // Round down to nearest radix
+        }
+    }
+
+    // Format first row
+    out += &format!("{:>20}+", "");
+    for idx in 0..radix {
+        out += &format!("{:>22}", idx);
+    }
+
+    for row in rows {
+        out += &format!("\n{:>20}: ", row);
+        for pos in row..row + radix {
+            let hl = get_is_highlighted(pos, v);
+            let curr_pos = if pos == m.pos { "<" } else { " " };
+            out += &format!(
+                " {}{:>20}{}{}", // Print the mem
+                hl.0, m.mem[pos], hl.1, curr_pos
+            );
+        }
+    }
+    out
+}
+
+// Formats the instruction at `pos' as e.g. `ADD *4 #3 ~-1', or `DATA 1234' when it is not an instruction
+// Returns the text and the number of cells it covers
+pub fn machine_op_at_to_string(m: &Machine, pos: usize) -> (String, usize) {
+    let op = if pos < MEM_LENGTH {
+        split_opcode(m.mem[pos])
+    } else {
+        None
+    };
+    match op {
+        Some(op) if pos + op.param_modes.len() < MEM_LENGTH => {
+            let mut out = opinfo_from_id(op.id).unwrap().name.to_string();
+            for (idx, mode) in op.param_modes.iter().enumerate() {
+                out += &format!(" {}{}", parammode_to_string(*mode), m.mem[pos + idx + 1]);
+            }
+            (out, op.param_modes.len() + 1)
+        }
+        _ => (format!("DATA {}", m.mem.get(pos).copied().unwrap_or(0)), 1),
+    }
+}
+
+// Linear sweep of the loaded program, one (position, text) pair per instruction
+pub fn disassemble(m: &Machine) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < m.len {
+        let (text, width) = machine_op_at_to_string(m, pos);
+        out.push((pos, text));
+        pos += width;
+    }
+    out
+}
+
+pub fn machine_state_to_string(m: &Machine, // Machine to print
+) -> String {
+    let mut out: String = "".to_string();
+    out += &format!("{:>6}Relative base = {}", "", m.relative_base);
+    out
+}
+
+pub fn opinfo_from_id(id: isize) -> Option<&'static OpInfo<'static>> {
+    OPS.iter().find(|op| op.id == id)
+}
+
+pub fn opinfo_from_name(name: &'static str) -> Option<&'static OpInfo<'static>> {
+    OPS.iter().find(|op| op.name == name)
+}
+
+pub fn split_opcode(op: isize) -> Option<Op> {
+    if op < 0 {
+        return None; // Negative values are never operations, but show up when disassembling data
+    }
+    let mut digits: Vec<_> = op
+        .to_string()
+        .chars()
+        .map(|d| d.to_digit(10).unwrap() as isize)
+        .collect();
+
+    if PRINT_DEBUG {
+        println!("{:?}", digits)
+    }
+
+    let mut opnum: isize;
+    if digits.len() < 2 {
+        opnum = digits.pop().unwrap();
+    } else {
+        opnum = digits.pop().unwrap();
+        opnum += digits.pop().unwrap() * 10;
+    }
+    if PRINT_DEBUG {
+        println!("opnum = {}", opnum)
+    }
+    let num_params = opinfo_from_id(opnum)?.n_params; // None when the op does not exist
+    if PRINT_DEBUG {
+        println!("num_params = {}", num_params)
+    }
+
+    let mut param_modes: Vec<ParamMode> = Vec::new();
+    for _ in 0..num_params {
+        let param_mode: Option<isize> = digits.pop();
+        if PRINT_DEBUG {
+            println!("matching {:?}", param_mode)
+        }
+        match param_mode {
+            Some(param_mode) => param_modes.push(to_param_mode(param_mode as usize)?),
+            None => param_modes.push(ParamMode::Indirect), // Missing digits should be leading zeroes
+        }
+    }
+
+    // param_modes.reverse();
+    Some(Op {
+        id: opnum,
+        param_modes,
+    })
+}
+
+pub fn load_machine_from_file(m: &mut Machine, file: &str) -> usize // Number of ints read
+{
+    let f = File::open(file).expect("Unable to open file");
+    let f = BufReader::new(f);
+
+    // Scan the lines of the file
+    for line in f.lines() {
+        let line = line.expect("Unable to read line");
+        let splits: Vec<&str> = line.split(',').collect();
+        for split in splits {
+            m.mem[m.len] = split.parse::<isize>().expect("Unable to parse split");
+            m.len += 1;
+            if m.len == MEM_LENGTH {
+                println!("Not enough memory to store program");
+                std::process::exit(-1);
+            }
+        }
+    }
+    m.high_water = m.high_water.max(m.len);
+    m.decode_cache.clear();
+    m.stopped_at = None;
+    m.len
+}
+
+pub fn load_machine_from_slice(m: &mut Machine, program: &[isize]) {
+    m.mem[..program.len()].copy_from_slice(program);
+    m.len = program.len();
+    m.high_water = m.high_water.max(m.len);
+    m.decode_cache.clear();
+    m.stopped_at = None;
+}
+
+fn state_hash(m: &Machine) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    m.pos.hash(&mut hasher);
+    m.relative_base.hash(&mut hasher);
+    m.mem[..m.high_water].hash(&mut hasher);
+    hasher.finish()
+}
+
+fn snapshot_matches(m: &Machine, s: &Snapshot) -> bool {
+    // The high water mark never decreases, so the snapshot is never longer than the memory in use
+    m.pos == s.pos
+        && m.relative_base == s.relative_base
+        && m.mem[..s.mem.len()] == s.mem[..]
+        && m.mem[s.mem.len()..m.high_water].iter().all(|&a| a == 0)
+}
+
+// Returns when the machine HALTs or on error
+// Also returns when the machine is out of input values in non-terminal mode,
+// when the step limit or deadline is reached, or when a loop is detected
+pub fn run_machine(m: &mut Machine) -> RunResult {
+    if m.len == 0 {
+        eprintln!("Error: Machine not loaded");
+        return RunResult::NotLoaded;
+    }
+    let mut highlight_pos: Vec<usize> = Vec::new();
+    let mut rows: BTreeSet<usize> = BTreeSet::new();
+    // Loop detection, state hash -> step count when first seen at a backward jump. Hashes seen
+    // twice keep a full snapshot each, a cycle with several backward jumps repeats all of them.
+    let mut seen_states: HashMap<u64, usize> = HashMap::new();
+    let mut loop_candidates: HashMap<u64, Snapshot> = HashMap::new();
+    m.halted = false;
+    let result = loop {
+        // Resuming from a stop executes the instruction it stopped on, even with a breakpoint there
+        let resumed_at = m.stopped_at.take();
+        if m.breakpoints.contains(&m.pos) && resumed_at != Some(m.pos) {
+            break RunResult::Breakpoint;
+        }
+        if m.step_limit.is_some_and(|limit| m.steps >= limit) {
+            if m.terminal {
+                println!("Machine reached the step limit, stopped")
+            }
+            break RunResult::StepLimit;
+        }
+        if m.steps.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && m.deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            if m.terminal {
+                println!("Machine passed the deadline, stopped")
+            }
+            break RunResult::DeadlinePassed;
+        }
+
+        if PRINT_DEBUG {
+            println!("\tmachine pc: {}", m.pos)
+        }
+        if m.interactive && m.terminal
+        // Print machine
+        {
+            // Collect the previous highlights
+            for pos in &highlight_pos {
+                rows.insert(*pos);
+            }
+
+            // Collect the new operands
+            if let Some(op) = split_opcode(m.mem[m.pos]) {
+                for (idx, mode) in op.param_modes.iter().enumerate() {
+                    rows.insert(unroll_parammode(m, m.pos + idx + 1, *mode));
+                }
+            }
+
+            // Print the machine
+            println!(
+                "{}",
+                machine_mem_to_string(m, Some(&highlight_pos), Some(&rows), None)
+            );
+            println!("{}", machine_state_to_string(m));
+            println!("{}", machine_pos_and_op_to_string(m));
+
+            println!("Press enter to execute next command");
+            print!("or send RUN to exit interactive mode > ");
+            std::io::stdout().flush().ok();
+            let mut line = String::new();
+            let _input = std::io::stdin()
+                .read_line(&mut line)
+                .expect("Failed to read line");
+            if line == "RUN\n" {
+                m.interactive = false;
+            }
+        }
+
+        // Run one step of the machine
+        if m.pos >= m.len {
+            if m.terminal {
+                println!("Machine reached end of memory, halted")
+            }
+            break RunResult::EndOfMemory;
+        }
+        match current_op(m) {
+            None => {
+                eprintln!("Error: Illegal operation");
+                break RunResult::IllegalOperation;
+            }
+            Some(op) => {
+                highlight_pos.clear();
+                if m.interactive {
+                    rows.clear();
+                }
+                let pos_before = m.pos;
+                let auto_inc = (opinfo_from_id(op.id).unwrap().func)(m, Some(&mut highlight_pos));
+                if auto_inc {
+                    m.pos += op.param_modes.len() + 1;
+                }
+                if PRINT_DEBUG {
+                    println!("highlight_pos is {:?}", highlight_pos)
+                }
+                if m.halted {
+                    if m.terminal {
+                        println!("Machine halted")
+                    }
+                    break if m.is_halted() {
+                        RunResult::Halted
+                    } else {
+                        RunResult::WaitingForInput
+                    };
+                }
+                m.steps += 1;
+                for pos in &highlight_pos {
+                    m.high_water = m.high_water.max(pos + 1);
+                    if let Some(cached) = m.decode_cache.get_mut(*pos) {
+                        *cached = None;
+                    }
+                }
+                m.last_writes.clone_from(&highlight_pos);
+
+                if m.loop_detection {
+                    if op.id == opinfo_from_name("IN").unwrap().id {
+                        // Consumed input makes the machine state differ from everything seen before
+                        seen_states.clear();
+                        loop_candidates.clear();
+                    } else if !auto_inc && m.pos <= pos_before {
+                        // Only backward jumps can close a cycle
+                        let hash = state_hash(m);
+                        if let Some(candidate) = loop_candidates.get(&hash) {
+                            if snapshot_matches(m, candidate) {
+                                if m.terminal {
+                                    println!("Machine is stuck in a loop, stopped")
+                                }
+                                break RunResult::Loop(LoopInfo {
+                                    pos: pos_before,
+                                    start: seen_states[&hash],
+                                    length: m.steps - candidate.step,
+                                });
+                            }
+                        }
+                        // A repeated hash is only a candidate until the full state repeats as well
+                        match seen_states.entry(hash) {
+                            Entry::Occupied(_) => {
+                                loop_candidates.insert(
+                                    hash,
+                                    Snapshot {
+                                        step: m.steps,
+                                        pos: m.pos,
+                                        relative_base: m.relative_base,
+                                        mem: m.mem[..m.high_water].to_vec(),
+                                    },
+                                );
+                            }
+                            Entry::Vacant(entry) => {
+                                entry.insert(m.steps);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+    m.stopped_at = Some(m.pos);
+    result
+}
+
+// The operation at pos, from the decode cache when possible
+fn current_op(m: &Machine) -> Option<Op> {
+    match m.decode_cache.get(m.pos) {
+        Some(Some(op)) => Some(op.clone()),
+        _ => split_opcode(m.mem[m.pos]),
+    }
+}
+
+fn unroll_parammode(
+    m: &Machine,
+    p: usize,        // The position to get
+    mode: ParamMode, // How to get it
+) -> usize {
+    match mode {
+        ParamMode::Indirect => m.mem[p] as usize,
+        ParamMode::Immediate => p,
+        ParamMode::Relative => (m.mem[p] + m.relative_base) as usize,
+    }
+}
+
+fn op_add(m: &mut Machine, v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+    let operand2 = m.mem[unroll_parammode(m, m.pos + 2, op.param_modes[1])];
+    let res = operand1 + operand2;
+
+    let actual_pos = unroll_parammode(m, m.pos + 3, op.param_modes[2]);
+    m.mem[actual_pos] = res;
+
+    // Highlight mutated position
+    if let Some(v) = v {
+        v.push(actual_pos);
+    }
+
+    true // Automatically increment the machine position
+}
+
+fn op_mult(m: &mut Machine, v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+    let operand2 = m.mem[unroll_parammode(m, m.pos + 2, op.param_modes[1])];
+    let res = operand1 * operand2;
+
+    let actual_pos = unroll_parammode(m, m.pos + 3, op.param_modes[2]);
+    m.mem[actual_pos] = res;
+
+    // Highlight mutated position
+    if let Some(v) = v {
+        v.push(actual_pos);
+    }
+
+    true // Automatically increment the machine position
+}
+
+fn op_in(m: &mut Machine, v: Option<&mut Vec<usize>>) -> bool {
+    if !m.terminal && m.inputs.is_empty() {
+        m.halted = true;
+        return false; // Do not automatically increment the machine position
+    } // Halt to simulate that it needs more input
+
+    let op = current_op(m).unwrap();
+    let actual_pos = unroll_parammode(m, m.pos + 1, op.param_modes[0]);
+
+    let read = if m.terminal {
+        print!(
+            "INPUT@{}->{}{} > ",
+            m.pos,
+            parammode_to_string(op.param_modes[0]),
+            m.mem[m.pos + 1]
+        );
+        std::io::stdout().flush().ok();
+        let mut line = String::new();
+        let _input = std::io::stdin()
+            .read_line(&mut line)
+            .expect("Failed to read line");
+        line.trim_end()
+            .parse::<isize>()
+            .expect("Unable to parse line")
+    } else {
+        m.inputs.pop_front().unwrap()
+    };
+
+    m.mem[actual_pos] = read;
+
+    // Highlight mutated position
+    if let Some(v) = v {
+        v.push(actual_pos);
+    }
+
+    true // Automatically increment the machine position
+}
+
+fn op_out(m: &mut Machine, _v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let out = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+
+    if m.terminal {
+        println!("OUTPUT@{} : {}", m.pos, out)
+    }
+    m.outputs.push_back(out);
+
+    true // Automatically increment the machine position
+}
+
+fn op_jit(m: &mut Machine, _v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+    let operand2 = m.mem[unroll_parammode(m, m.pos + 2, op.param_modes[1])];
+
+    if operand1 != 0 {
+        m.pos = operand2 as usize;
+        false // Do not automatically increment the machine position
+    } else {
+        true // Automatically increment the machine position
+    }
+}
+
+fn op_jif(m: &mut Machine, _v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+    let operand2 = m.mem[unroll_parammode(m, m.pos + 2, op.param_modes[1])];
+
+    if operand1 == 0 {
+        m.pos = operand2 as usize;
+        false // Do not automatically increment the machine position
+    } else {
+        true // Automatically increment the machine position
+    }
+}
+
+fn op_less(m: &mut Machine, v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+    let operand2 = m.mem[unroll_parammode(m, m.pos + 2, op.param_modes[1])];
+    let val = if operand1 < operand2 { 1 } else { 0 };
+
+    let actual_pos = unroll_parammode(m, m.pos + 3, op.param_modes[2]);
+    m.mem[actual_pos] = val;
+    if let Some(v) = v {
+        v.push(actual_pos);
+    }
+
+    true // Automatically increment the machine position
+}
+
+fn op_eq(m: &mut Machine, v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+    let operand2 = m.mem[unroll_parammode(m, m.pos + 2, op.param_modes[1])];
+    let val = if operand1 == operand2 { 1 } else { 0 };
+
+    let actual_pos = unroll_parammode(m, m.pos + 3, op.param_modes[2]);
+    m.mem[actual_pos] = val;
+    if let Some(v) = v {
+        v.push(actual_pos);
+    }
+
+    true // Automatically increment the machine position
+}
+
+fn op_rbase(m: &mut Machine, _v: Option<&mut Vec<usize>>) -> bool {
+    let op = current_op(m).unwrap();
+    let operand1 = m.mem[unroll_parammode(m, m.pos + 1, op.param_modes[0])];
+
+    m.relative_base += operand1;
+    // We keep this below for now, in case the above naive solution does not work (unclear from spec)
+    // if operand1 > 0 {
+    //     m.relative_base += operand1 as usize;
+    // }
+    // else {
+    //     m.relative_base -= -operand1 as usize;
+    // }
+
+    true // Automatically increment the machine position
+}
+
+fn op_halt(m: &mut Machine, _v: Option<&mut Vec<usize>>) -> bool {
+    m.halted = true;
+    false // Do not automatically increment the machine position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_limit() {
+        // Jump-if-true to itself, forever
+        let mut m = Machine::new();
+        load_machine_from_slice(&mut m, &[1105, 1, 0]);
+        m.set_step_limit(Some(100));
+        assert_eq!(run_machine(&mut m), RunResult::StepLimit);
+        assert_eq!(m.steps(), 100);
+    }
+
+    #[test]
+    fn test_loop_detection() {
+        // Counts mem[15] up to 3, then spins on the jump back to 4 with mem[15] unchanged
+        let mut m = Machine::new();
+        load_machine_from_slice(
+            &mut m,
+            &[
+                1007, 15, 3, 16, 1006, 16, 4, 101, 1, 15, 15, 1105, 1, 0, 99, 0, 0,
+            ],
+        );
+        m.set_loop_detection(true);
+        match run_machine(&mut m) {
+            RunResult::Loop(info) => {
+                assert_eq!(info.pos, 4);
+                assert_eq!(info.length, 1);
+            }
+            other => panic!("Expected a loop, got {:?}", other),
+        }
+
+        // A program that halts is not reported as a loop
+        let mut m = Machine::new();
+        load_machine_from_slice(
+            &mut m,
+            &[101, 1, 13, 13, 1007, 13, 5, 14, 1005, 14, 0, 99, 0, 0, 0],
+        );
+        m.set_loop_detection(true);
+        assert_eq!(run_machine(&mut m), RunResult::Halted);
+        assert_eq!(m.mem[13], 5);
+    }
+
+    #[test]
+    fn test_nested_loop_detection() {
+        // The inner loop jumps back to 4 once and the outer loop jumps back to 0, every turn
+        let mut m = Machine::new();
+        load_machine_from_slice(
+            &mut m,
+            &[
+                1101, 0, 0, 20, 101, 1, 20, 20, 1007, 20, 2, 21, 1005, 21, 4, 1105, 1, 0, 99, 0, 0,
+                0,
+            ],
+        );
+        m.set_loop_detection(true);
+        match run_machine(&mut m) {
+            RunResult::Loop(info) => {
+                assert_eq!(info.pos, 12);
+                assert_eq!(info.start, 4); // After the first jump back to 4
+                assert_eq!(info.length, 8);
+            }
+            other => panic!("Expected a loop, got {:?}", other),
+        }
+        assert!(m.steps() < 100);
+    }
+
+    #[test]
+    fn test_input_is_not_a_loop() {
+        // Echoes input until it reads a zero
+        let mut m = Machine::new();
+        load_machine_from_slice(&mut m, &[3, 9, 4, 9, 1005, 9, 0, 99, 0, 0]);
+        m.set_loop_detection(true);
+        for _ in 0..3 {
+            m.put_input(7);
+        }
+        assert_eq!(run_machine(&mut m), RunResult::WaitingForInput);
+        m.put_input(0);
+        assert_eq!(run_machine(&mut m), RunResult::Halted);
+        assert_eq!(m.output_waiting(), 4);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        // Outputs 1, 2 and 3 with one instruction each, then halts
+        let mut m = Machine::new();
+        load_machine_from_slice(&mut m, &[104, 1, 104, 2, 104, 3, 99]);
+        m.set_breakpoints([0, 4].iter().copied().collect());
+        assert_eq!(run_machine(&mut m), RunResult::Breakpoint); // Before anything runs
+        assert_eq!((m.pos, m.output_waiting()), (0, 0));
+        assert_eq!(run_machine(&mut m), RunResult::Breakpoint);
+        assert_eq!((m.pos, m.output_waiting()), (4, 2));
+
+        // A step limit stop on a breakpoint resumes past it as well
+        m.set_step_limit(Some(m.steps()));
+        assert_eq!(run_machine(&mut m), RunResult::StepLimit);
+        m.set_step_limit(None);
+        assert_eq!(run_machine(&mut m), RunResult::Halted);
+        assert_eq!(m.output_waiting(), 3);
+
+        // Loading a program again hits the breakpoint at the start again
+        load_machine_from_slice(&mut m, &[104, 1, 104, 2, 104, 3, 99]);
+        m.reset();
+        assert_eq!(run_machine(&mut m), RunResult::Breakpoint);
+        assert_eq!(m.pos, 0);
+    }
+
+    #[test]
+    fn test_decode_cache_drops_overwritten_ops() {
+        // Turns the second output into a halt before reaching it
+        let mut m = Machine::new();
+        load_machine_from_slice(&mut m, &[1101, 1, 98, 6, 104, 5, 104, 7, 99]);
+        m.set_decode_cache(vec![0, 4, 6, 8]);
+        assert_eq!(run_machine(&mut m), RunResult::Halted);
+        assert_eq!(m.get_output(), Some(5));
+        assert_eq!(m.get_output(), None);
+    }
+}
@@ -0,0 +1,346 @@
+// Ahead of time translation of an Intcode program into a Rust module
+//
+// Every reachable instruction becomes straight line code over a memory array, grouped into
+// blocks that start at jump targets and input instructions. Jumps set `pc' and go back to a
+// `match' on it. Parameters in cells the program writes to are read from memory at runtime,
+// everything else is inlined. Programs that may overwrite an instruction before running it
+// are refused, writes through relative addresses are checked against the code at runtime.
+use crate::analysis::*;
+use crate::machine::*;
+use std::collections::BTreeSet;
+
+struct Emitter<'a> {
+    m: &'a Machine,
+    written: BTreeSet<usize>, // Cells some instruction writes to directly
+    out: String,
+}
+
+impl Emitter<'_> {
+    fn line(&mut self, indent: usize, text: &str) {
+        for _ in 0..indent {
+            self.out += "    ";
+        }
+        self.out += text;
+        self.out += "\n";
+    }
+
+    // The raw parameter, a literal unless the program changes it
+    fn raw(&self, cell: usize) -> String {
+        if self.written.contains(&cell) {
+            format!("mem[{}]", cell)
+        } else {
+            self.m.mem[cell].to_string()
+        }
+    }
+
+    fn address(&self, instr: &Instruction, idx: usize) -> String {
+        let cell = instr.pos + idx + 1;
+        match instr.param_modes[idx] {
+            ParamMode::Indirect if !self.written.contains(&cell) => self.m.mem[cell].to_string(),
+            ParamMode::Indirect => format!("mem[{}] as usize", cell),
+            ParamMode::Relative => format!("(rb + {}) as usize", self.raw(cell)),
+            ParamMode::Immediate => unreachable!(),
+        }
+    }
+
+    fn read(&self, instr: &Instruction, idx: usize) -> String {
+        match instr.param_modes[idx] {
+            ParamMode::Immediate => self.raw(instr.pos + idx + 1),
+            _ => format!("mem[{}]", self.address(instr, idx)),
+        }
+    }
+
+    fn write(&mut self, indent: usize, instr: &Instruction, idx: usize, value: &str) {
+        let cell = instr.pos + idx + 1;
+        if instr.param_modes[idx] == ParamMode::Indirect && !self.written.contains(&cell) {
+            // Known target, the analysis has checked that it does not change code that runs later
+            let line = format!("mem[{}] = {};", self.m.mem[cell], value);
+            self.line(indent, &line);
+        } else {
+            let lines = [
+                format!("let addr = {};", self.address(instr, idx)),
+                "if is_code(addr) {".to_string(),
+                format!("    pc = {};", instr.pos),
+                "    break Exit::SelfModified(addr);".to_string(),
+                "}".to_string(),
+                format!("mem[addr] = {};", value),
+            ];
+            self.line(indent, "{");
+            for line in &lines {
+                self.line(indent + 1, line);
+            }
+            self.line(indent, "}");
+        }
+    }
+
+    // Emits one instruction, returns whether control can fall through to the next one
+    fn instruction(&mut self, indent: usize, instr: &Instruction) -> bool {
+        let text = machine_op_at_to_string(self.m, instr.pos).0;
+        self.line(indent, &format!("// {:>6}: {}", instr.pos, text));
+        match instr.name() {
+            "ADD" | "MULT" | "LESS" | "EQ" => {
+                let (a, b) = (self.read(instr, 0), self.read(instr, 1));
+                let value = match instr.name() {
+                    "ADD" => format!("{} + {}", a, b),
+                    "MULT" => format!("{} * {}", a, b),
+                    "LESS" => format!("({} < {}) as isize", a, b),
+                    _ => format!("({} == {}) as isize", a, b),
+                };
+                self.write(indent, instr, 2, &value);
+            }
+            "IN" => {
+                let lines = [
+                    "let value = match io.input() {".to_string(),
+                    "    Some(value) => value,".to_string(),
+                    "    None => {".to_string(),
+                    format!("        pc = {};", instr.pos),
+                    "        break Exit::WaitingForInput;".to_string(),
+                    "    }".to_string(),
+                    "};".to_string(),
+                ];
+                for line in &lines {
+                    self.line(indent, line);
+                }
+                self.write(indent, instr, 0, "value");
+            }
+            "OUT" => {
+                let line = format!("io.output({});", self.read(instr, 0));
+                self.line(indent, &line);
+            }
+            "JIT" | "JIF" => {
+                let cmp = if instr.name() == "JIT" { "!=" } else { "==" };
+                let line = format!("if {} {} 0 {{", self.read(instr, 0), cmp);
+                self.line(indent, &line);
+                let line = format!("pc = {};", self.read(instr, 1));
+                self.line(indent + 1, &line);
+                self.line(indent + 1, "continue;");
+                self.line(indent, "}");
+            }
+            "RBASE" => {
+                let line = format!("rb += {};", self.read(instr, 0));
+                self.line(indent, &line);
+            }
+            "HALT" => {
+                self.line(indent, &format!("pc = {};", instr.pos));
+                self.line(indent, "break Exit::Halted;");
+                return false;
+            }
+            _ => unreachable!(),
+        }
+        true
+    }
+}
+
+// Leaders start a block, everything a jump can land on and every input, which is where
+// `run' resumes after waiting
+fn leaders(a: &Analysis) -> BTreeSet<usize> {
+    let mut leaders: BTreeSet<usize> = a.jump_targets.clone();
+    leaders.insert(0);
+    for instr in a.instructions.values() {
+        match instr.name() {
+            "JIT" | "JIF" => {
+                leaders.insert(instr.pos + instr.width());
+            }
+            "IN" => {
+                leaders.insert(instr.pos);
+            }
+            _ => {}
+        }
+    }
+    leaders
+}
+
+fn ranges_to_pattern(cells: &BTreeSet<usize>) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cell in cells {
+        match ranges.last_mut() {
+            Some(last) if last.1 + 1 == cell => last.1 = cell,
+            _ => ranges.push((cell, cell)),
+        }
+    }
+    if ranges.is_empty() {
+        return "_ if false".to_string();
+    }
+    ranges
+        .iter()
+        .map(|(start, end)| format!("{}..={}", start, end))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// Returns the generated module, or why the program can not be compiled ahead of time
+pub fn transpile(m: &Machine, source: &str) -> Result<String, String> {
+    let a = analyse(m);
+    if a.is_self_modifying() {
+        let writes: Vec<String> = a
+            .code_writes
+            .iter()
+            .filter(|w| w.opcode && w.runs_again)
+            .map(|w| format!("{} writes {}", w.writer, w.target))
+            .collect();
+        return Err(format!(
+            "{} modifies its own code: {}",
+            source,
+            writes.join(", ")
+        ));
+    }
+
+    let mut e = Emitter {
+        m,
+        written: a.direct_writes.keys().copied().collect(),
+        out: String::new(),
+    };
+
+    // Everything that is inlined into the generated code
+    let mut code: BTreeSet<usize> = BTreeSet::new();
+    for instr in a.instructions.values() {
+        code.insert(instr.pos);
+        for cell in instr.pos + 1..instr.pos + instr.width() {
+            if !e.written.contains(&cell) {
+                code.insert(cell);
+            }
+        }
+    }
+
+    let program: Vec<String> = m.mem[..m.len()].iter().map(|v| v.to_string()).collect();
+    let header = format!(
+        "// Generated by intcode-aot from {}, do not edit
+
+pub const MEM_LENGTH: usize = 10000;
+const LEN: isize = {};
+const PROGRAM: [isize; {}] = [{}];
+
+pub trait Io {{
+    fn input(&mut self) -> Option<isize>;
+    fn output(&mut self, value: isize);
+}}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Exit {{
+    Halted,
+    WaitingForInput, // Call `run' again once more input is available
+    EndOfMemory,
+    IllegalOperation,
+    BadJump(isize),      // Jumped somewhere the analysis found no code
+    SelfModified(usize), // A write at runtime hit code that was compiled ahead of time
+}}
+
+pub struct Program {{
+    pub mem: Vec<isize>,
+    pub pc: isize,
+    pub relative_base: isize,
+}}
+
+#[allow(dead_code)]
+fn is_code(addr: usize) -> bool {{
+    matches!(addr, {})
+}}
+
+impl Program {{
+    pub fn new() -> Self {{
+        let mut mem = vec![0; MEM_LENGTH];
+        mem[..PROGRAM.len()].copy_from_slice(&PROGRAM);
+        Program {{
+            mem,
+            pc: 0,
+            relative_base: 0,
+        }}
+    }}
+
+    #[allow(unused_parens, unused_mut, unused_variables, clippy::all)]
+    pub fn run(&mut self, io: &mut impl Io) -> Exit {{
+        let mem = &mut self.mem[..];
+        let mut pc = self.pc;
+        let mut rb = self.relative_base;
+        let exit = loop {{
+            match pc {{",
+        source,
+        m.len(),
+        m.len(),
+        program.join(", "),
+        ranges_to_pattern(&code)
+    );
+    e.out += &header;
+    e.out += "\n";
+
+    let leaders = leaders(&a);
+    let mut block_start: Option<usize> = None;
+    let instructions: Vec<Instruction> = a.instructions.values().cloned().collect();
+    for (idx, instr) in instructions.iter().enumerate() {
+        if block_start.is_none() || leaders.contains(&instr.pos) {
+            if block_start.is_some() {
+                // Fall through into the next block
+                e.line(5, &format!("pc = {};", instr.pos));
+                e.line(4, "}");
+            }
+            e.line(4, &format!("{} => {{", instr.pos));
+            block_start = Some(instr.pos);
+        }
+        let falls_through = e.instruction(5, instr);
+        let next = instr.pos + instr.width();
+        let next_is_adjacent = instructions
+            .get(idx + 1)
+            .is_some_and(|n| n.pos == next && !leaders.contains(&n.pos));
+        if !falls_through || !next_is_adjacent {
+            if falls_through {
+                e.line(5, &format!("pc = {};", next));
+            }
+            e.line(4, "}");
+            block_start = None;
+        }
+    }
+    for pos in &a.illegal {
+        e.line(4, &format!("{} => break Exit::IllegalOperation,", pos));
+    }
+
+    let footer = "                _ if pc >= LEN => break Exit::EndOfMemory,
+                _ => break Exit::BadJump(pc),
+            }
+        };
+        self.pc = pc;
+        self.relative_base = rb;
+        exit
+    }
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+";
+    e.out += footer;
+    Ok(e.out)
+}
+
+// A `main' that runs the program with inputs from the command line, printing like `intcode-run'
+pub fn harness() -> String {
+    "
+struct ArgsIo {
+    inputs: std::collections::VecDeque<isize>,
+}
+
+impl Io for ArgsIo {
+    fn input(&mut self) -> Option<isize> {
+        self.inputs.pop_front()
+    }
+    fn output(&mut self, value: isize) {
+        println!(\"{}\", value);
+    }
+}
+
+fn main() {
+    let mut io = ArgsIo {
+        inputs: std::env::args()
+            .skip(1)
+            .map(|arg| arg.parse().expect(\"Unable to parse input\"))
+            .collect(),
+    };
+    let mut program = Program::new();
+    let exit = program.run(&mut io);
+    println!(\"exit: {:?}\", exit);
+    println!(\"mem[0]: {}\", program.mem[0]);
+}
+"
+    .to_string()
+}
@@ -0,0 +1,119 @@
+// Programs compiled with `transpile' against the same programs run by `run_machine'. Every
+// program is compiled once with rustc, then run with each set of inputs, and has to print
+// exactly what `intcode-run' prints.
+
+use intcode::analysis::*;
+use intcode::machine::*;
+use intcode::transpile::*;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn path(program: &str) -> String {
+    format!("{}/{}", env!("CARGO_MANIFEST_DIR"), program)
+}
+
+fn load(program: &str) -> Machine {
+    let mut m = Machine::new();
+    load_machine_from_file(&mut m, &path(program));
+    m
+}
+
+// What `intcode-run' prints
+fn reference(program: &str, inputs: &[isize]) -> String {
+    let mut m = load(program);
+    m.set_terminal(false);
+    m.set_interactive(false);
+    m.set_decode_cache(analyse(&m).instructions.keys().copied());
+    for input in inputs {
+        m.put_input(*input);
+    }
+    let result = run_machine(&mut m);
+    let mut out = String::new();
+    while let Some(output) = m.get_output() {
+        out += &format!("{}\n", output);
+    }
+    out += &format!("exit: {:?}\n", result);
+    out += &format!("mem[0]: {}\n", m.mem[0]);
+    out
+}
+
+fn compile(program: &str) -> Result<PathBuf, String> {
+    let code = transpile(&load(program), program)? + &harness();
+    let name = program.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let source = dir.join(format!("{}.rs", name));
+    let binary = dir.join(&name);
+    std::fs::write(&source, code).expect("Unable to write the generated module");
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let status = Command::new(rustc)
+        .args(["--edition", "2018", "-O", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .status()
+        .expect("Unable to run rustc");
+    assert!(
+        status.success(),
+        "The module for {} does not compile",
+        program
+    );
+    Ok(binary)
+}
+
+fn check(program: &str, runs: &[&[isize]]) {
+    let binary = compile(program).unwrap();
+    for inputs in runs {
+        let output = Command::new(&binary)
+            .args(inputs.iter().map(|input| input.to_string()))
+            .output()
+            .expect("Unable to run the compiled program");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            reference(program, inputs),
+            "{} with inputs {:?}",
+            program,
+            inputs
+        );
+    }
+}
+
+#[test]
+fn test_conformance() {
+    check("conformance/day2_example.intcode", &[&[]]);
+    for program in &[
+        "conformance/day5_eq8_position.intcode",
+        "conformance/day5_lt8_immediate.intcode",
+        "conformance/day5_compare8.intcode",
+    ] {
+        check(program, &[&[7], &[8], &[9]]);
+    }
+    for program in &[
+        "conformance/day5_jump_position.intcode",
+        "conformance/day5_jump_immediate.intcode",
+    ] {
+        check(program, &[&[0], &[1]]);
+    }
+    check("conformance/day9_quine.intcode", &[&[]]);
+    check("conformance/day9_large_mult.intcode", &[&[]]);
+    check("conformance/day9_large_out.intcode", &[&[]]);
+}
+
+#[test]
+fn test_puzzle_inputs() {
+    check("../day2/input.txt", &[&[]]);
+    check("../day7/input.txt", &[&[0, 0]]);
+    check("../day9/input.txt", &[&[1], &[2]]);
+    check("../day11/input.txt", &[&[0]]);
+    check("../day13/input.txt", &[&[]]);
+    check("../day15/input.txt", &[&[1]]);
+}
+
+#[test]
+fn test_refuses_self_modifying() {
+    // The day 5 diagnostic program patches the instruction at 6 before running it
+    let error = compile("../day5/input.txt").unwrap_err();
+    assert!(
+        error.ends_with("modifies its own code: 2 writes 6"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,244 @@
+// Scripted sessions against the `intcode-dap' binary, every request is sent up front and the
+// replies are checked in order once the server has exited
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+
+// The day 5 example that outputs 1 when the input equals 8, instructions at 0, 2, 6 and 8
+const EQ8: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/conformance/day5_eq8_position.intcode"
+);
+
+// Writes `code' to a program file for the server to launch
+fn program(name: &str, code: &str) -> String {
+    let path = format!("{}/{}.intcode", env!("CARGO_TARGET_TMPDIR"), name);
+    std::fs::write(&path, code).expect("Unable to write the program");
+    path
+}
+
+fn session(requests: &[(&str, Value)]) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_intcode-dap"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Unable to start intcode-dap");
+    {
+        let stdin = child.stdin.as_mut().unwrap();
+        for (seq, (command, arguments)) in requests.iter().enumerate() {
+            let text = json!({
+                "seq": seq + 1,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            })
+            .to_string();
+            write!(stdin, "Content-Length: {}\r\n\r\n{}", text.len(), text).unwrap();
+        }
+    }
+    drop(child.stdin.take());
+
+    let mut out = BufReader::new(child.stdout.take().unwrap());
+    let mut messages = Vec::new();
+    loop {
+        let mut header = String::new();
+        if out.read_line(&mut header).unwrap() == 0 {
+            break;
+        }
+        let len: usize = header
+            .trim()
+            .strip_prefix("Content-Length:")
+            .expect("Expected a Content-Length header")
+            .trim()
+            .parse()
+            .unwrap();
+        out.read_line(&mut String::new()).unwrap(); // The blank line
+        let mut body = vec![0; len];
+        out.read_exact(&mut body).unwrap();
+        messages.push(serde_json::from_slice(&body).unwrap());
+    }
+    assert!(child.wait().unwrap().success());
+    messages
+}
+
+// The next message, which must be a successful response to `command'
+fn response<'a>(messages: &mut impl Iterator<Item = &'a Value>, command: &str) -> &'a Value {
+    let message = messages.next().expect("Ran out of messages");
+    assert_eq!(message["type"], "response", "{}", message);
+    assert_eq!(message["command"], command, "{}", message);
+    assert_eq!(message["success"], true, "{}", message);
+    &message["body"]
+}
+
+fn event<'a>(messages: &mut impl Iterator<Item = &'a Value>, event: &str) -> &'a Value {
+    let message = messages.next().expect("Ran out of messages");
+    assert_eq!(message["type"], "event", "{}", message);
+    assert_eq!(message["event"], event, "{}", message);
+    &message["body"]
+}
+
+fn variable<'a>(variables: &'a Value, name: &str) -> &'a Value {
+    &variables["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == name)
+        .unwrap_or_else(|| panic!("No variable {}", name))["value"]
+}
+
+#[test]
+fn test_session() {
+    let messages = session(&[
+        ("initialize", json!({ "adapterID": "intcode" })),
+        (
+            "launch",
+            json!({ "program": EQ8, "inputs": [8], "stopOnEntry": true }),
+        ),
+        (
+            "setInstructionBreakpoints",
+            json!({ "breakpoints": [
+                { "instructionReference": "4" },
+                { "instructionReference": "8" },
+                { "instructionReference": "11" },
+            ]}),
+        ),
+        ("configurationDone", json!({})),
+        ("next", json!({})),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("continue", json!({ "threadId": 1 })),
+        ("variables", json!({ "variablesReference": 1 })),
+        ("variables", json!({ "variablesReference": 1000 })),
+        ("continue", json!({ "threadId": 1 })),
+        ("continue", json!({ "threadId": 1 })),
+        ("disconnect", json!({})),
+    ]);
+    let mut messages = messages.iter();
+
+    let capabilities = response(&mut messages, "initialize");
+    assert_eq!(capabilities["supportsInstructionBreakpoints"], true);
+    response(&mut messages, "launch");
+    event(&mut messages, "initialized");
+
+    // 4 is inside the EQ at 2, so it moves to the OUT at 6. 11 is past the end.
+    let breakpoints = &response(&mut messages, "setInstructionBreakpoints")["breakpoints"];
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[0]["instructionReference"], "6");
+    assert!(breakpoints[0]["message"].is_string());
+    assert_eq!(breakpoints[1]["verified"], true);
+    assert_eq!(breakpoints[1]["instructionReference"], "8");
+    assert!(breakpoints[1]["message"].is_null());
+    assert_eq!(breakpoints[2]["verified"], false);
+
+    response(&mut messages, "configurationDone");
+    assert_eq!(event(&mut messages, "stopped")["reason"], "entry");
+    response(&mut messages, "next");
+    assert_eq!(event(&mut messages, "stopped")["reason"], "step");
+    let frame = &response(&mut messages, "stackTrace")["stackFrames"][0];
+    assert_eq!(frame["instructionPointerReference"], "2");
+    assert_eq!(frame["name"], "EQ *9 *10 *9");
+
+    response(&mut messages, "continue");
+    assert_eq!(event(&mut messages, "stopped")["reason"], "breakpoint");
+    let registers = response(&mut messages, "variables");
+    assert_eq!(variable(registers, "pc"), "6");
+    assert_eq!(variable(registers, "steps"), "2");
+    assert_eq!(variable(registers, "inputs"), "[]");
+    let memory = response(&mut messages, "variables");
+    assert_eq!(variable(memory, "[9]"), "1"); // 8 equals 8
+    assert_eq!(variable(memory, "[6]"), "4 <");
+
+    response(&mut messages, "continue");
+    assert_eq!(event(&mut messages, "output")["output"], "OUTPUT : 1\n");
+    assert_eq!(event(&mut messages, "stopped")["reason"], "breakpoint");
+    response(&mut messages, "continue");
+    assert_eq!(event(&mut messages, "exited")["exitCode"], 0);
+    event(&mut messages, "terminated");
+    response(&mut messages, "disconnect");
+    assert!(messages.next().is_none());
+}
+
+#[test]
+fn test_breakpoint_on_entry() {
+    // A breakpoint on the first instruction stops before anything runs
+    let messages = session(&[
+        ("initialize", json!({})),
+        ("launch", json!({ "program": EQ8, "inputs": [7] })),
+        ("setBreakpoints", json!({ "breakpoints": [{ "line": 1 }] })),
+        ("configurationDone", json!({})),
+        ("variables", json!({ "variablesReference": 1 })),
+        ("continue", json!({ "threadId": 1 })),
+        ("disconnect", json!({})),
+    ]);
+    let mut messages = messages.iter();
+    response(&mut messages, "initialize");
+    response(&mut messages, "launch");
+    event(&mut messages, "initialized");
+    let breakpoints = &response(&mut messages, "setBreakpoints")["breakpoints"];
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[0]["instructionReference"], "0");
+    response(&mut messages, "configurationDone");
+    assert_eq!(event(&mut messages, "stopped")["reason"], "breakpoint");
+    let registers = response(&mut messages, "variables");
+    assert_eq!(variable(registers, "pc"), "0");
+    assert_eq!(variable(registers, "inputs"), "[7]");
+    response(&mut messages, "continue");
+    assert_eq!(event(&mut messages, "output")["output"], "OUTPUT : 0\n");
+    assert_eq!(event(&mut messages, "exited")["exitCode"], 0);
+    event(&mut messages, "terminated");
+    response(&mut messages, "disconnect");
+}
+
+#[test]
+fn test_pause_and_disconnect_while_running() {
+    // Counts mem[7] up forever, the state never repeats so only a pause stops it
+    let counter = program("counter", "101,1,7,7,1105,1,0,0");
+    let messages = session(&[
+        ("initialize", json!({})),
+        ("launch", json!({ "program": counter })),
+        ("configurationDone", json!({})),
+        ("pause", json!({ "threadId": 1 })),
+        ("variables", json!({ "variablesReference": 1 })),
+        ("continue", json!({ "threadId": 1 })),
+        ("disconnect", json!({})),
+    ]);
+    let mut messages = messages.iter();
+    response(&mut messages, "initialize");
+    response(&mut messages, "launch");
+    event(&mut messages, "initialized");
+    response(&mut messages, "configurationDone");
+    response(&mut messages, "pause");
+    assert_eq!(event(&mut messages, "stopped")["reason"], "pause");
+    let registers = response(&mut messages, "variables");
+    let steps: usize = variable(registers, "steps")
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(steps >= 100_000, "Paused after {} steps", steps);
+    response(&mut messages, "continue");
+    response(&mut messages, "disconnect");
+    assert!(messages.next().is_none());
+}
+
+#[test]
+fn test_end_of_memory_is_an_exception() {
+    // Adds and runs off the end instead of halting
+    let add = program("add", "1101,1,1,5");
+    let messages = session(&[
+        ("initialize", json!({})),
+        ("launch", json!({ "program": add })),
+        ("configurationDone", json!({})),
+        ("disconnect", json!({})),
+    ]);
+    let mut messages = messages.iter();
+    response(&mut messages, "initialize");
+    response(&mut messages, "launch");
+    event(&mut messages, "initialized");
+    response(&mut messages, "configurationDone");
+    let stopped = event(&mut messages, "stopped");
+    assert_eq!(stopped["reason"], "exception");
+    assert_eq!(stopped["description"], "Ran past the end of the program");
+    response(&mut messages, "disconnect");
+    assert!(messages.next().is_none());
+}
@@ -0,0 +1,79 @@
+// Reads the capital letters Advent of Code draws, 4 pixels wide and 6 high with one blank column
+// between them. `#' is a lit pixel, anything else is blank.
+
+pub const LETTER_WIDTH: usize = 4;
+pub const LETTER_HEIGHT: usize = 6;
+const LETTER_PITCH: usize = LETTER_WIDTH + 1;
+const FONT: [(char, [&str; LETTER_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]), // Y spills into the blank column
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+// The text in `rows', every letter has a lit left column so cropping the image to the lit pixels
+// lines the letters up with the first column
+pub fn read(rows: &[Vec<bool>]) -> Result<String, String> {
+    if rows.len() != LETTER_HEIGHT {
+        return Err(format!(
+            "Letters are {} pixels high, got {} rows",
+            LETTER_HEIGHT,
+            rows.len()
+        ));
+    }
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for start in (0..width).step_by(LETTER_PITCH) {
+        let glyph: Vec<String> = rows
+            .iter()
+            .map(|row| {
+                (start..start + LETTER_WIDTH)
+                    .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        if glyph.iter().all(|row| !row.contains('#')) {
+            continue; // Padding
+        }
+        match FONT.iter().find(|(_, font)| font.iter().eq(glyph.iter())) {
+            Some((letter, _)) => out.push(*letter),
+            None => {
+                return Err(format!(
+                    "Unknown letter at column {}:\n{}",
+                    start,
+                    glyph.join("\n")
+                ))
+            }
+        }
+    }
+    Ok(out)
+}
+
+// Draws `text' the way `read' expects it
+pub fn draw(text: &str) -> Result<Vec<Vec<bool>>, String> {
+    let mut rows = vec![Vec::new(); LETTER_HEIGHT];
+    for c in text.chars() {
+        let (_, font) = FONT
+            .iter()
+            .find(|(letter, _)| *letter == c)
+            .ok_or(format!("No glyph for `{}'", c))?;
+        for (row, glyph_row) in rows.iter_mut().zip(font.iter()) {
+            row.extend(glyph_row.chars().map(|p| p == '#'));
+            row.push(false);
+        }
+    }
+    Ok(rows)
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+// Every set of `k' different indices into `values' where the values add up to `target'. The
+// indices of a set are increasing and the sets are sorted, so equal values at different indices
+// give different sets.
+pub fn k_sum(values: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+    let mut out = match k {
+        _ if k > values.len() => Vec::new(),
+        0 if target == 0 => vec![Vec::new()],
+        0 => Vec::new(),
+        1 => (0..values.len())
+            .filter(|i| values[*i] == target)
+            .map(|i| vec![i])
+            .collect(),
+        2 => two_sum(values, target),
+        3 => three_sum(values, target),
+        _ => meet_in_the_middle(values, k, target),
+    };
+    for set in out.iter_mut() {
+        set.sort_unstable();
+    }
+    out.sort_unstable();
+    out
+}
+
+// Every index seen so far by value, each index looks up the one value it pairs up with
+fn two_sum(values: &[i64], target: i64) -> Vec<Vec<usize>> {
+    let mut seen: HashMap<i64, Vec<usize>> = HashMap::new();
+    let mut out = Vec::new();
+    for (j, value) in values.iter().enumerate() {
+        if let Some(others) = seen.get(&(target - value)) {
+            out.extend(others.iter().map(|i| vec![*i, j]));
+        }
+        seen.entry(*value).or_default().push(j);
+    }
+    out
+}
+
+// Sorted by value, the first index is fixed and the other two close in from both ends of what is
+// after it. Runs of equal values are taken all at once, so every set is found exactly once.
+fn three_sum(values: &[i64], target: i64) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|i| values[*i]);
+    let value = |pos: usize| values[order[pos]];
+    let mut out = Vec::new();
+    for first in 0..order.len() {
+        let rest = target - value(first);
+        let (mut lo, mut hi) = (first + 1, order.len() - 1);
+        while lo < hi {
+            let sum = value(lo) + value(hi);
+            if sum < rest {
+                lo += 1;
+            } else if sum > rest {
+                hi -= 1;
+            } else if value(lo) == value(hi) {
+                // Every pair in between has the same sum
+                for a in lo..hi {
+                    for b in a + 1..=hi {
+                        out.push(vec![order[first], order[a], order[b]]);
+                    }
+                }
+                break;
+            } else {
+                let lo_end = (lo..=hi).find(|p| value(*p) != value(lo)).unwrap();
+                let hi_start = (lo..=hi).rev().find(|p| value(*p) != value(hi)).unwrap() + 1;
+                for a in lo..lo_end {
+                    for b in hi_start..=hi {
+                        out.push(vec![order[first], order[a], order[b]]);
+                    }
+                }
+                lo = lo_end;
+                hi = hi_start - 1;
+            }
+        }
+    }
+    out
+}
+
+// Calls `f' with every increasing set of `k' indices below `n'
+fn for_each_combination<F: FnMut(&[usize])>(n: usize, k: usize, f: &mut F) {
+    fn recurse<F: FnMut(&[usize])>(n: usize, k: usize, set: &mut Vec<usize>, f: &mut F) {
+        if set.len() == k {
+            f(set);
+            return;
+        }
+        let from = set.last().map_or(0, |last| last + 1);
+        // Leave room for the indices still to come
+        for i in from..=n - (k - set.len()) {
+            set.push(i);
+            recurse(n, k, set, f);
+            set.pop();
+        }
+    }
+    recurse(n, k, &mut Vec::with_capacity(k), f);
+}
+
+// A set split into its lower `k / 2' indices and the rest. The lower halves are kept by sum and
+// sorted by their highest index, so an upper half only pairs with the ones entirely below it.
+fn meet_in_the_middle(values: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+    let n = values.len();
+    let low_k = k / 2;
+    let mut lower: HashMap<i64, Vec<Vec<usize>>> = HashMap::new();
+    for_each_combination(n, low_k, &mut |set| {
+        let sum = set.iter().map(|i| values[*i]).sum();
+        lower.entry(sum).or_default().push(set.to_vec());
+    });
+    for sets in lower.values_mut() {
+        sets.sort_by_key(|set| *set.last().unwrap());
+    }
+    let mut out = Vec::new();
+    for_each_combination(n, k - low_k, &mut |set| {
+        let sum: i64 = set.iter().map(|i| values[*i]).sum();
+        if let Some(sets) = lower.get(&(target - sum)) {
+            let below = sets.partition_point(|low| *low.last().unwrap() < set[0]);
+            for low in &sets[..below] {
+                out.push(low.iter().chain(set.iter()).copied().collect());
+            }
+        }
+    });
+    out
+}
@@ -1,46 +1,122 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-fn main() {
-    {
-        // Part 1
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut ans = 0;
-        let mut vec = Vec::new();
-        'outer1: for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            let entry = line.parse::<i32>().expect("Unable to parse line");
-            for e in &vec {
-                if entry + e == 2020 {
-                    ans = entry * e;
-                    break 'outer1;
-                }
+pub mod ksum;
+use ksum::k_sum;
+
+// Every set of `k' indices tried one by one, to check `k_sum' against
+fn brute_force(values: &[i64], k: usize, target: i64) -> Vec<Vec<usize>> {
+    fn recurse(
+        values: &[i64],
+        k: usize,
+        target: i64,
+        set: &mut Vec<usize>,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if set.len() == k {
+            if set.iter().map(|i| values[*i]).sum::<i64>() == target {
+                out.push(set.clone());
             }
-            vec.push(entry);
+            return;
+        }
+        for i in set.last().map_or(0, |last| last + 1)..values.len() {
+            set.push(i);
+            recurse(values, k, target, set, out);
+            set.pop();
         }
-        println!("Part 1: Ans is: {}", ans);
     }
+    let mut out = Vec::new();
+    recurse(values, k, target, &mut Vec::new(), &mut out);
+    out
+}
+
+// Linear congruential, `count' numbers from `low' up to but not including `high'
+fn generate(seed: u64, count: usize, low: i64, high: i64) -> Vec<i64> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            low + ((state >> 33) % (high - low) as u64) as i64
+        })
+        .collect()
+}
 
+fn product(values: &[i64], set: &[usize]) -> i64 {
+    set.iter().map(|i| values[*i]).product()
+}
+
+fn main() {
     {
-        // Part 2
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut ans = 0;
-        let mut vec = Vec::new();
-        'outer2: for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            let entry = line.parse::<i32>().expect("Unable to parse line");
-            for e1 in &vec {
-                for e2 in &vec {
-                    if entry + e1 + e2 == 2020 {
-                        ans = entry * e1 * e2;
-                        break 'outer2;
-                    }
+        // Tests
+        let example = [1721, 979, 366, 299, 675, 1456];
+        assert_eq!(k_sum(&example, 2, 2020), vec![vec![0, 3]]);
+        assert_eq!(product(&example, &[0, 3]), 514579);
+        assert_eq!(k_sum(&example, 3, 2020), vec![vec![1, 2, 4]]);
+        assert_eq!(product(&example, &[1, 2, 4]), 241861950);
+        assert_eq!(k_sum(&example, 1, 979), vec![vec![1]]);
+        assert_eq!(k_sum(&example, 0, 0), vec![Vec::<usize>::new()]);
+        assert!(k_sum(&example, 7, 2020).is_empty());
+        assert_eq!(
+            k_sum(&example, 6, example.iter().sum()),
+            vec![vec![0, 1, 2, 3, 4, 5]]
+        );
+
+        // An index is only used once, but equal values at different indices are different
+        assert!(k_sum(&[1010], 2, 2020).is_empty());
+        assert_eq!(
+            k_sum(&[1010, 1010, 1010], 2, 2020),
+            vec![vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+        assert_eq!(k_sum(&[0, 0, 0, 0], 3, 0).len(), 4);
+        assert_eq!(
+            k_sum(&[1, 1, 2, 2], 3, 5),
+            vec![vec![0, 2, 3], vec![1, 2, 3]]
+        );
+        assert_eq!(k_sum(&[-5, 5, 0, 10], 3, 5), vec![vec![0, 2, 3]]);
+        assert_eq!(k_sum(&[-5, 5, 0, 10], 2, 0), vec![vec![0, 1]]);
+        assert_eq!(k_sum(&[-5, 5, 0, 10, -10], 4, 0), vec![vec![0, 1, 3, 4]]);
+
+        for seed in 0..20 {
+            let values = generate(seed, 12, -6, 7);
+            for k in 0..=6 {
+                for target in -4..=4 {
+                    assert_eq!(k_sum(&values, k, target), brute_force(&values, k, target));
                 }
             }
-            vec.push(entry);
         }
-        println!("Part 2: Ans is: {}", ans);
+
+        // Larger inputs, far too many sets to try one by one
+        let values = generate(1, 5000, -1_000_000, 1_000_000);
+        let pairs = k_sum(&values, 2, 2020);
+        assert!(pairs
+            .iter()
+            .all(|set| values[set[0]] + values[set[1]] == 2020));
+        let values = generate(2, 800, -100_000, 100_000);
+        let triples = k_sum(&values, 3, 2020);
+        assert!(!triples.is_empty());
+        assert!(triples
+            .iter()
+            .all(|set| set.iter().map(|i| values[*i]).sum::<i64>() == 2020));
+        let values = generate(3, 150, -1000, 1000);
+        let quadruples = k_sum(&values, 4, 2020);
+        assert!(!quadruples.is_empty());
+        assert!(quadruples
+            .iter()
+            .all(|set| set.iter().map(|i| values[*i]).sum::<i64>() == 2020));
+    }
+
+    let f = File::open("input.txt").expect("Unable to open file");
+    let f = BufReader::new(f);
+    let mut values = Vec::new();
+    for line in f.lines() {
+        let line = line.expect("Unable to read line");
+        values.push(line.trim().parse::<i64>().expect("Unable to parse line"));
+    }
+    for (part, k) in [(1, 2), (2, 3)].iter() {
+        let sets = k_sum(&values, *k, 2020);
+        assert_eq!(sets.len(), 1, "Expected exactly one set of {} entries", k);
+        println!("Part {}: Ans is: {}", part, product(&values, &sets[0]));
     }
 }
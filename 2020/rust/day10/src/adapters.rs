@@ -0,0 +1,154 @@
+use rand::distributions::uniform::SampleUniform;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::{AddAssign, SubAssign};
+
+// The rises an adapter takes in the puzzle, 1 to 3 jolts
+pub const STEPS: [u64; 3] = [1, 2, 3];
+
+// The outlet, every adapter and the device, sorted by joltage. The device is always rated 3 jolts
+// above the highest adapter.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Adapters {
+    joltages: Vec<u64>,
+}
+
+impl Adapters {
+    // One adapter joltage per line
+    pub fn parse(lines: &[String]) -> Result<Adapters, String> {
+        let mut joltages = vec![0];
+        for line in lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            joltages.push(
+                line.parse::<u64>()
+                    .map_err(|_| format!("`{}' is not a joltage", line))?,
+            );
+        }
+        joltages.sort_unstable();
+        joltages.push(joltages.last().unwrap() + 3);
+        Ok(Adapters { joltages })
+    }
+
+    pub fn from_file(file: &str) -> Result<Adapters, String> {
+        let f = File::open(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        let lines: Result<Vec<String>, _> = BufReader::new(f).lines().collect();
+        Adapters::parse(&lines.map_err(|e| format!("Unable to read {}: {}", file, e))?)
+    }
+
+    pub fn joltages(&self) -> &[u64] {
+        &self.joltages
+    }
+
+    // Rise -> times it is seen when every adapter is used, from the outlet to the device
+    pub fn differences(&self) -> BTreeMap<u64, usize> {
+        let mut out = BTreeMap::new();
+        for pair in self.joltages.windows(2) {
+            *out.entry(pair[1] - pair[0]).or_insert(0) += 1;
+        }
+        out
+    }
+
+    // Every later joltage that `i' plugs into, in order. The joltages are sorted so this stops
+    // at the first rise larger than all of `steps'.
+    fn next<'a>(&'a self, i: usize, steps: &'a [u64]) -> impl Iterator<Item = usize> + 'a {
+        let largest = steps.iter().copied().max().unwrap_or(0);
+        (i + 1..self.joltages.len())
+            .take_while(move |j| self.joltages[*j] - self.joltages[i] <= largest)
+            .filter(move |j| steps.contains(&(self.joltages[*j] - self.joltages[i])))
+    }
+
+    // Number of chains from every joltage to the device, filled in from the device backwards
+    fn ways<T>(&self, steps: &[u64]) -> Vec<T>
+    where
+        T: Clone + From<u8> + for<'a> AddAssign<&'a T>,
+    {
+        let n = self.joltages.len();
+        let mut ways = vec![T::from(0); n];
+        ways[n - 1] = T::from(1);
+        for i in (0..n - 1).rev() {
+            let mut sum = T::from(0);
+            for j in self.next(i, steps) {
+                sum += &ways[j];
+            }
+            ways[i] = sum;
+        }
+        ways
+    }
+
+    // Number of ways to chain some of the adapters from the outlet to the device, where every
+    // link rises by one of `steps'. Works for `u128' and for `BigUint' when that overflows.
+    pub fn arrangements<T>(&self, steps: &[u64]) -> T
+    where
+        T: Clone + From<u8> + for<'a> AddAssign<&'a T>,
+    {
+        self.ways::<T>(steps).swap_remove(0)
+    }
+
+    // The `n'th chain, counting the chains that take lower adapters first before the others.
+    // `ways' are the counts `ways' gives for the same `steps'.
+    fn unrank<T: Count>(&self, steps: &[u64], ways: &[T], n: &T) -> Option<Vec<u64>> {
+        if *n >= ways[0] {
+            return None;
+        }
+        let mut n = n.clone();
+        let mut i = 0;
+        let mut chain = vec![self.joltages[0]];
+        while i < self.joltages.len() - 1 {
+            for j in self.next(i, steps) {
+                if n < ways[j] {
+                    i = j;
+                    break;
+                }
+                n -= &ways[j];
+            }
+            chain.push(self.joltages[i]);
+        }
+        Some(chain)
+    }
+
+    // The `n'th chain in the order of `chains', `None' when there are no more than `n' chains
+    pub fn nth_chain<T: Count>(&self, steps: &[u64], n: &T) -> Option<Vec<u64>> {
+        self.unrank(steps, &self.ways(steps), n)
+    }
+
+    // Every chain, the ones that take lower adapters first before the others. The chains are
+    // counted once, in `T' so there can be more of them than fit in `u128'.
+    pub fn chains<'a, T: Count + 'a>(
+        &'a self,
+        steps: &'a [u64],
+    ) -> impl Iterator<Item = Vec<u64>> + 'a {
+        let ways = self.ways::<T>(steps);
+        let mut n = T::from(0);
+        std::iter::from_fn(move || {
+            let chain = self.unrank(steps, &ways, &n)?;
+            n += &T::from(1);
+            Some(chain)
+        })
+    }
+
+    // Any of the chains, all as likely
+    pub fn sample<T: Count + SampleUniform, R: Rng>(
+        &self,
+        steps: &[u64],
+        rng: &mut R,
+    ) -> Option<Vec<u64>> {
+        let ways = self.ways::<T>(steps);
+        if ways[0] == T::from(0) {
+            return None;
+        }
+        let n = rng.gen_range(T::from(0)..ways[0].clone());
+        self.unrank(steps, &ways, &n)
+    }
+}
+
+// What chains are counted in when walking them, `u128' or `BigUint'
+pub trait Count:
+    Clone + From<u8> + PartialOrd + for<'a> AddAssign<&'a Self> + for<'a> SubAssign<&'a Self>
+{
+}
+
+impl<T> Count for T where
+    T: Clone + From<u8> + PartialOrd + for<'a> AddAssign<&'a T> + for<'a> SubAssign<&'a T>
+{
+}
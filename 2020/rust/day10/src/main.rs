@@ -1,223 +1,96 @@
-// use std::cmp;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use num_bigint::BigUint;
 
-fn get_differences(v: Vec<usize>) -> Vec<usize> {
-    let mut out = Vec::new();
-    for i in 0..v.len() {
-        if i == 0 {
-            continue; // cannot calculate difference from the last
-        }
-        let index = v[i] - v[i - 1];
-        // println!(
-        //     "diff: i = {:?}, v[i] = {:?}, v[i - 1] = {:?}, diff = {:?}",
-        //     i,
-        //     v[i],
-        //     v[i - 1],
-        //     index
-        // );
-        if index > out.len() {
-            out.resize(index + 1, 0);
-        }
-        out[v[i] - v[i - 1]] += 1;
-    }
-    out
-}
-
-// fn permutate(v: Vec<usize>) -> usize {
-//     let mut out = 0;
-//     // println!("Permutating: \t{:?}", v);
-//     if v.len() == 1 {
-//         out += 1;
-//     }
-//     if v.len() > 1 && v[1] - v[0] <= 3 {
-//         // println!("Recurse1: \t{:?}", v[1..].to_vec());
-//         out += permutate(v[1..].to_vec());
-//     }
-//     if v.len() > 2 && v[2] - v[0] <= 3 {
-//         // println!("Recurse2: \t{:?}", v[2..].to_vec());
-//         out += permutate(v[2..].to_vec());
-//     }
-//     if v.len() > 3 && v[3] - v[0] <= 3 {
-//         // println!("Recurse3: \t{:?}", v[3..].to_vec());
-//         out += permutate(v[3..].to_vec());
-//     }
-//     // println!("Permutating: \t{:?}, out: {:?}", v, out);
-//     out
-// }
-
-fn permutate2(v: Vec<usize>) -> usize {
-    let mut out = 1;
-    let mut i = 0;
-    loop {
-        println!("out: {:?}, i: {:?}", out, i);
-        let mut perms = 0;
-        let mut skips = 0;
+pub mod adapters;
+use adapters::{Adapters, STEPS};
 
-        if i < v.len() - 3 {
-            match (
-                v[i + 1] == v[i] + 1,
-                v[i + 2] == v[i] + 2,
-                v[i + 3] == v[i] + 3,
-            ) {
-                (true, true, true) => {
-                    perms = 4;
-                    skips = 4;
-                }
-                (true, true, false) => {
-                    perms = 2;
-                    skips = 3;
-                }
-                (true, false, true) => {
-                    perms = 2;
-                    skips = 3;
-                }
-                (true, false, false) => {
-                    perms = 1;
-                    skips = 2;
-                }
-                (false, true, true) => {
-                    perms = 2;
-                    skips = 3;
-                }
-                (false, true, false) => {
-                    perms = 1;
-                    skips = 2;
-                }
-                (false, false, true) => {
-                    perms = 1;
-                    skips = 2;
-                }
-                (false, false, false) => {
-                    println!("Should never happen3");
-                }
-            }
-        } else if i < v.len() - 2 {
-            match (v[i + 1] == v[i] + 1, v[i + 2] == v[i] + 2) {
-                (true, true) => {
-                    perms = 2;
-                    skips = 3;
-                }
-                (true, false) => {
-                    perms = 1;
-                    skips = 2;
-                }
-                (false, true) => {
-                    perms = 1;
-                    skips = 2;
-                }
-                (false, false) => {
-                    println!("Should never happen2");
-                }
-            }
-        } else if i < v.len() - 1 {
-            match v[i + 1] == v[i] + 1 {
-                true => {
-                    perms = 1;
-                    skips = 2;
-                }
-                false => {
-                    println!("Should never happen1");
-                }
-            }
-        }
-        // println!("v[i + 3] ({:?}) - v[i + 0] ({:?})", v[i + 3], perms);
-        println!("skips: {:?}, perms: {:?}", skips, perms);
-
-        out *= perms;
-        i += skips;
-        if i >= v.len() {
-            break;
-        }
-    }
-    out
+fn adapters(list: &[u64]) -> Adapters {
+    let lines: Vec<String> = list.iter().map(|j| j.to_string()).collect();
+    Adapters::parse(&lines).unwrap()
 }
 
+// Usage: day10 [--sample]
 fn main() {
     {
         // Tests
-        let f = File::open("test1.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut vec = Vec::new();
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            let entry = line.parse::<usize>().expect("Unable to parse line");
-            vec.push(entry);
+        let test1 = Adapters::from_file("test1.txt").unwrap();
+        let diff = test1.differences();
+        assert_eq!(diff[&1], 7);
+        assert_eq!(diff[&3], 5);
+        assert_eq!(test1.arrangements::<u128>(&STEPS), 8);
+        let chains: Vec<Vec<u64>> = test1.chains::<u128>(&STEPS).collect();
+        assert_eq!(chains.len(), 8);
+        assert_eq!(
+            chains[0],
+            vec![0, 1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19, 22]
+        );
+        assert_eq!(chains[7], vec![0, 1, 4, 7, 10, 12, 15, 16, 19, 22]);
+        assert_eq!(test1.nth_chain(&STEPS, &8u128), None);
+
+        let test2 = Adapters::from_file("test2.txt").unwrap();
+        let diff = test2.differences();
+        assert_eq!(diff[&1], 22);
+        assert_eq!(diff[&3], 10);
+        assert_eq!(test2.arrangements::<u128>(&STEPS), 19208);
+        assert_eq!(test2.chains::<u128>(&STEPS).count(), 19208);
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let chain = test2.sample::<u128, _>(&STEPS, &mut rng).unwrap();
+            assert!(chain.windows(2).all(|p| STEPS.contains(&(p[1] - p[0]))));
         }
-        vec.push(0);
-        vec.sort();
-        vec.push(vec.last().unwrap() + 3);
-        let diff = get_differences(vec);
-        println!("test 1: {:?}", diff);
-        assert!(diff[1] == 7);
-        assert!(diff[3] == 5);
-    }
-    {
-        // Tests
-        let f = File::open("test2.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut vec = Vec::new();
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            let entry = line.parse::<usize>().expect("Unable to parse line");
-            vec.push(entry);
+
+        // Other steps: 0 1 2 3 6
+        let small = adapters(&[3, 1, 2]);
+        assert_eq!(small.arrangements::<u128>(&STEPS), 4);
+        assert_eq!(small.arrangements::<u128>(&[1, 3]), 2);
+        assert_eq!(small.arrangements::<u128>(&[3]), 1);
+        assert_eq!(small.arrangements::<u128>(&[1, 2]), 0); // The device is 3 above
+        assert_eq!(small.sample::<u128, _>(&[1, 2], &mut rng), None);
+        assert_eq!(small.arrangements::<u128>(&[1, 2, 3, 4, 5, 6]), 8); // Any of 1 2 3
+        assert_eq!(
+            small.chains::<u128>(&[1, 3]).collect::<Vec<_>>(),
+            vec![vec![0, 1, 2, 3, 6], vec![0, 3, 6]]
+        );
+        // Rises of 1 to 3 along every joltage up to 300 overflows `u128'
+        let ladder = adapters(&(1..=300).collect::<Vec<u64>>());
+        let mut expected = vec![
+            BigUint::from(1u32),
+            BigUint::from(1u32),
+            BigUint::from(2u32),
+        ];
+        for n in 3..=300 {
+            let next = &expected[n - 1] + &expected[n - 2] + &expected[n - 3];
+            expected.push(next);
         }
-        vec.push(0);
-        vec.sort();
-        vec.push(vec.last().unwrap() + 3);
-        let diff = get_differences(vec);
-        // println!("test 2: {:?}", diff);
-        assert!(diff[1] == 22);
-        assert!(diff[3] == 10);
-    }
-    {
-        // Part 1
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut vec = Vec::new();
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            let entry = line.parse::<usize>().expect("Unable to parse line");
-            vec.push(entry);
+        assert_eq!(ladder.arrangements::<BigUint>(&STEPS), expected[300]);
+        let mut chains = ladder.chains::<BigUint>(&STEPS);
+        assert_eq!(chains.next(), Some(ladder.joltages().to_vec()));
+        assert_eq!(chains.next().unwrap().len(), ladder.joltages().len() - 1);
+        let last: Vec<u64> = (0..=101).map(|j| j * 3).collect(); // Every rise is 3
+        let n = &expected[300] - 1u32;
+        assert_eq!(ladder.nth_chain(&STEPS, &n), Some(last));
+        assert_eq!(ladder.nth_chain(&STEPS, &expected[300]), None);
+        for _ in 0..10 {
+            let chain = ladder.sample::<BigUint, _>(&STEPS, &mut rng).unwrap();
+            assert_eq!(chain.last(), Some(&303));
+            assert!(chain.windows(2).all(|p| STEPS.contains(&(p[1] - p[0]))));
         }
-        vec.push(0);
-        vec.sort();
-        vec.push(vec.last().unwrap() + 3);
-        let diff = get_differences(vec);
-        println!("Part 1: diffs: {:?}", diff);
-        println!("Part 1: Ans is: {}", diff[1] * diff[3]);
+        let shorter = adapters(&(1..=50).collect::<Vec<u64>>());
+        assert_eq!(
+            BigUint::from(shorter.arrangements::<u128>(&STEPS)),
+            expected[50]
+        );
     }
     {
-        // Tests
-        let f = File::open("test1.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut vec = Vec::new();
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            let entry = line.parse::<usize>().expect("Unable to parse line");
-            vec.push(entry);
+        let adapters = Adapters::from_file("input.txt").unwrap();
+        let diff = adapters.differences();
+        println!("Part 1: diffs: {:?}", diff);
+        println!("Part 1: Ans is: {}", diff[&1] * diff[&3]);
+        println!("Part 2: Ans is: {}", adapters.arrangements::<u128>(&STEPS));
+        if std::env::args().any(|arg| arg == "--sample") {
+            let chain = adapters
+                .sample::<u128, _>(&STEPS, &mut rand::thread_rng())
+                .unwrap();
+            println!("{:?}", chain);
         }
-        vec.push(0);
-        vec.sort();
-        vec.push(vec.last().unwrap() + 3);
-        let perm = permutate2(vec);
-        println!("test 1: {:?}", perm);
-        assert!(perm == 8);
-    }
-    {
-        // Part 2
-        // let f = File::open("input.txt").expect("Unable to open file");
-        // let f = BufReader::new(f);
-        // let mut vec = Vec::new();
-        // for line in f.lines() {
-        //     let line = line.expect("Unable to read line");
-        //     let entry = line.parse::<usize>().expect("Unable to parse line");
-        //     vec.push(entry);
-        // }
-        // vec.push(0);
-        // vec.sort();
-        // vec.push(vec.last().unwrap() + 3);
-        // let perm = permutate(vec);
-        // println!("Part 2: Ans is: {}", perm);
     }
 }
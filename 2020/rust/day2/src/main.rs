@@ -1,63 +1,80 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+pub mod password;
+use password::{PasswordEntry, Policy, SledRental, Toboggan};
 
+fn count_valid(entries: &[PasswordEntry], policy: &dyn Policy) -> usize {
+    entries
+        .iter()
+        .filter(|entry| policy.check(entry).valid)
+        .count()
+}
+
+// Usage: day2 [--why]
 fn main() {
     {
-        // Part 1
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut num_valids = 0;
-        for line in f.lines() {
-            let mut input_splits = Vec::new();
-            let line = line.expect("Unable to read line");
-            for s in line.split(' ') {
-                input_splits.push(s.trim());
-            }
-            // 14-15 h: hhhhhhhhhhhhhjh
-            let bounds: Vec<&str> = input_splits[0].split('-').collect();
-            let low_bound = bounds[0].parse::<i32>().expect("Unable to parse line");
-            let high_bound = bounds[1].parse::<i32>().expect("Unable to parse line");
-            let range = low_bound..high_bound + 1;
-            let char = input_splits[1].chars().nth(0).unwrap();
-            let pw = input_splits[2];
-            let mut occurrences = 0;
-            for c in pw.chars() {
-                if c == char {
-                    occurrences += 1;
-                }
-            }
-            if range.contains(&occurrences) {
-                num_valids += 1;
+        // Tests
+        let entries: Vec<PasswordEntry> = ["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc"]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| PasswordEntry::parse(i + 1, line).unwrap())
+            .collect();
+        assert_eq!(
+            entries[0],
+            PasswordEntry {
+                line: 1,
+                low: 1,
+                high: 3,
+                letter: 'a',
+                password: "abcde".to_string()
             }
-        }
-        println!("Part 1: Num valids: {}", num_valids);
-    }
+        );
+        assert_eq!(entries[2].to_string(), "2-9 c: ccccccccc");
+        assert_eq!(count_valid(&entries, &SledRental), 2);
+        assert_eq!(count_valid(&entries, &Toboggan), 1);
+        assert_eq!(
+            SledRental.check(&entries[1]).reason,
+            "b is in it 0 times, 1 to 3 allowed"
+        );
+        assert_eq!(Toboggan.check(&entries[0]).reason, "a at 1, c at 3");
+        assert_eq!(Toboggan.check(&entries[2]).reason, "c at both 2 and 9");
 
-    {
-        // Part 2
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut num_valids = 0;
-        for line in f.lines() {
-            let mut input_splits = Vec::new();
-            let line = line.expect("Unable to read line");
-            for s in line.split(' ') {
-                input_splits.push(s.trim());
-            }
-            // 14-15 h: hhhhhhhhhhhhhjh
-            let positions: Vec<&str> = input_splits[0].split('-').collect();
-            let low_pos = positions[0].parse::<usize>().expect("Unable to parse line");
-            let high_pos = positions[1].parse::<usize>().expect("Unable to parse line");
+        let past_end = PasswordEntry::parse(1, "2-20 a: aaa").unwrap();
+        assert!(Toboggan.check(&past_end).valid);
+        assert_eq!(Toboggan.check(&past_end).reason, "a at 2, nothing at 20");
+        let zero = PasswordEntry::parse(1, "0-1 a: a").unwrap();
+        assert!(Toboggan.check(&zero).valid);
 
-            let char = input_splits[1].chars().nth(0).unwrap();
-            let pw = input_splits[2];
+        assert!(PasswordEntry::parse(1, "1-3 a abcde").is_err());
+        assert!(PasswordEntry::parse(1, "1-3-4 a: abcde").is_err());
+        assert!(PasswordEntry::parse(1, "1 a: abcde").is_err());
+        assert!(PasswordEntry::parse(1, "x-3 a: abcde").is_err());
+        assert!(PasswordEntry::parse(1, "1-3 ab: abcde").is_err());
+        assert!(PasswordEntry::parse(1, "1-3 : abcde").is_err());
+        assert!(PasswordEntry::parse(1, "1-3 a: åbcde").is_err());
+    }
 
-            if (pw.chars().nth(low_pos - 1).unwrap() == char)
-                ^ (pw.chars().nth(high_pos - 1).unwrap() == char)
-            {
-                num_valids += 1;
+    let entries = PasswordEntry::from_file("input.txt").unwrap();
+    let why = std::env::args().any(|arg| arg == "--why");
+    let policies: [&dyn Policy; 2] = [&SledRental, &Toboggan];
+    for (part, policy) in policies.iter().enumerate() {
+        if why {
+            for entry in &entries {
+                let report = policy.check(entry);
+                if !report.valid {
+                    println!(
+                        "Part {}: Line {}: {}: {}",
+                        part + 1,
+                        entry.line,
+                        entry,
+                        report.reason
+                    );
+                }
             }
         }
-        println!("Part 1: Num valids: {}", num_valids);
+        println!(
+            "Part {}: Num valids ({}): {}",
+            part + 1,
+            policy.name(),
+            count_valid(&entries, *policy)
+        );
     }
 }
@@ -0,0 +1,151 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// `14-15 h: hhhhhhhhhhhhhjh', what the two numbers mean is up to the policy
+#[derive(Clone, PartialEq, Debug)]
+pub struct PasswordEntry {
+    pub line: usize, // Counted from 1
+    pub low: usize,
+    pub high: usize,
+    pub letter: char,
+    pub password: String, // ASCII only, so letters can be looked up by byte
+}
+
+impl PasswordEntry {
+    pub fn parse(line: usize, s: &str) -> Result<PasswordEntry, String> {
+        let bad = || {
+            format!(
+                "Line {}: expected `<low>-<high> <letter>: <password>', got `{}'",
+                line, s
+            )
+        };
+        let mut splits = s.trim().splitn(2, ": ");
+        let (policy, password) = match (splits.next(), splits.next()) {
+            (Some(policy), Some(password)) => (policy, password.trim()),
+            _ => return Err(bad()),
+        };
+        let mut splits = policy.split(' ');
+        let (range, letter) = match (splits.next(), splits.next(), splits.next()) {
+            (Some(range), Some(letter), None) => (range, letter),
+            _ => return Err(bad()),
+        };
+        let mut splits = range.split('-');
+        let (low, high) = match (splits.next(), splits.next(), splits.next()) {
+            (Some(low), Some(high), None) => (low, high),
+            _ => return Err(bad()),
+        };
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("Line {}: `{}' is not a number", line, n))
+        };
+        let mut letters = letter.chars();
+        let letter = match (letters.next(), letters.next()) {
+            (Some(c), None) if c.is_ascii() => c,
+            _ => return Err(format!("Line {}: `{}' is not one letter", line, letter)),
+        };
+        if !password.is_ascii() {
+            return Err(format!("Line {}: `{}' is not ASCII", line, password));
+        }
+        Ok(PasswordEntry {
+            line,
+            low: number(low)?,
+            high: number(high)?,
+            letter,
+            password: password.to_string(),
+        })
+    }
+
+    pub fn from_file(file: &str) -> Result<Vec<PasswordEntry>, String> {
+        let f = File::open(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        let mut out = Vec::new();
+        for (i, line) in BufReader::new(f).lines().enumerate() {
+            let line = line.map_err(|e| format!("Unable to read {}: {}", file, e))?;
+            if !line.trim().is_empty() {
+                out.push(PasswordEntry::parse(i + 1, &line)?);
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl fmt::Display for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.low, self.high, self.letter, self.password
+        )
+    }
+}
+
+// Whether an entry follows a policy, and why
+#[derive(Clone, PartialEq, Debug)]
+pub struct Report {
+    pub valid: bool,
+    pub reason: String,
+}
+
+pub trait Policy {
+    fn name(&self) -> &str;
+    fn check(&self, entry: &PasswordEntry) -> Report;
+}
+
+// The letter is in the password between `low' and `high' times, both included
+pub struct SledRental;
+
+impl Policy for SledRental {
+    fn name(&self) -> &str {
+        "sled rental"
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Report {
+        let count = entry
+            .password
+            .chars()
+            .filter(|c| *c == entry.letter)
+            .count();
+        Report {
+            valid: (entry.low..=entry.high).contains(&count),
+            reason: format!(
+                "{} is in it {} times, {} to {} allowed",
+                entry.letter, count, entry.low, entry.high
+            ),
+        }
+    }
+}
+
+// The letter is at exactly one of the positions `low' and `high', counted from 1. A position past
+// the end of the password does not have the letter.
+pub struct Toboggan;
+
+impl Policy for Toboggan {
+    fn name(&self) -> &str {
+        "toboggan"
+    }
+
+    fn check(&self, entry: &PasswordEntry) -> Report {
+        let at = |pos: usize| -> (bool, String) {
+            match pos
+                .checked_sub(1)
+                .and_then(|i| entry.password.as_bytes().get(i))
+            {
+                Some(c) => (
+                    *c as char == entry.letter,
+                    format!("{} at {}", *c as char, pos),
+                ),
+                None => (false, format!("nothing at {}", pos)),
+            }
+        };
+        let (low, low_reason) = at(entry.low);
+        let (high, high_reason) = at(entry.high);
+        let reason = match (low, high) {
+            (true, true) => format!("{} at both {} and {}", entry.letter, entry.low, entry.high),
+            _ => format!("{}, {}", low_reason, high_reason),
+        };
+        Report {
+            valid: low ^ high,
+            reason,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// The map of trees, which repeats forever to the left and to the right
+#[derive(Clone, PartialEq, Debug)]
+pub struct Forest {
+    width: usize,
+    trees: Vec<Vec<bool>>, // Row by row from the top, true where there is a `#'
+}
+
+impl Forest {
+    pub fn parse(lines: &[String]) -> Result<Forest, String> {
+        let mut trees: Vec<Vec<bool>> = Vec::new();
+        for (i, line) in lines.iter().map(|l| l.trim()).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let row = line
+                .chars()
+                .map(|c| match c {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    _ => Err(format!("Line {}: expected `#' or `.', got `{}'", i + 1, c)),
+                })
+                .collect::<Result<Vec<bool>, String>>()?;
+            if let Some(first) = trees.first() {
+                if row.len() != first.len() {
+                    return Err(format!(
+                        "Line {}: {} wide, the first row is {} wide",
+                        i + 1,
+                        row.len(),
+                        first.len()
+                    ));
+                }
+            }
+            trees.push(row);
+        }
+        match trees.first() {
+            Some(first) => Ok(Forest {
+                width: first.len(),
+                trees,
+            }),
+            None => Err("No rows in the map".to_string()),
+        }
+    }
+
+    pub fn from_file(file: &str) -> Result<Forest, String> {
+        let f = File::open(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        let lines: Result<Vec<String>, _> = BufReader::new(f).lines().collect();
+        Forest::parse(&lines.map_err(|e| format!("Unable to read {}: {}", file, e))?)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.trees.len()
+    }
+
+    // Any `x' is on the map, it wraps around to the width of one copy
+    pub fn is_tree(&self, x: isize, y: usize) -> bool {
+        self.trees[y][x.rem_euclid(self.width as isize) as usize]
+    }
+
+    // Every position from the top left until below the bottom row, `right' is negative for
+    // going left
+    pub fn path(&self, right: isize, down: usize) -> Result<Vec<(isize, usize)>, String> {
+        if down == 0 {
+            return Err(format!("Right {} down 0 never reaches the bottom", right));
+        }
+        Ok((0..self.height())
+            .step_by(down)
+            .enumerate()
+            .map(|(step, y)| (step as isize * right, y))
+            .collect())
+    }
+
+    pub fn trees_on_slope(&self, right: isize, down: usize) -> Result<usize, String> {
+        Ok(self
+            .path(right, down)?
+            .iter()
+            .filter(|(x, y)| self.is_tree(*x, *y))
+            .count())
+    }
+
+    // The slope with `right' up to `max_right' either way and `down' from 1 to `max_down' that
+    // hits the fewest trees, and how many. The first one found on a tie, trying the slopes with
+    // the least `down' and then the least `right' first.
+    pub fn best_slope(&self, max_right: isize, max_down: usize) -> Option<((isize, usize), usize)> {
+        let mut best: Option<((isize, usize), usize)> = None;
+        for down in 1..=max_down {
+            for right in -max_right..=max_right {
+                let trees = self.trees_on_slope(right, down).unwrap();
+                if best.is_none() || trees < best.unwrap().1 {
+                    best = Some(((right, down), trees));
+                }
+            }
+        }
+        best
+    }
+
+    // The map with the path marked like the puzzle text does, `O' where it is open and `X' on a
+    // tree. Repeated sideways as many times as the path needs.
+    pub fn render(&self, right: isize, down: usize) -> Result<String, String> {
+        let path: HashSet<(isize, usize)> = self.path(right, down)?.into_iter().collect();
+        let width = self.width as isize;
+        let first_copy = path.iter().map(|(x, _)| x.div_euclid(width)).min().unwrap();
+        let last_copy = path.iter().map(|(x, _)| x.div_euclid(width)).max().unwrap();
+        let mut out = String::new();
+        for y in 0..self.height() {
+            for x in first_copy * width..(last_copy + 1) * width {
+                let tree = self.is_tree(x, y);
+                out.push(match (path.contains(&(x, y)), tree) {
+                    (true, true) => 'X',
+                    (true, false) => 'O',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                });
+            }
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
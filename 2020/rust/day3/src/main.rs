@@ -1,68 +1,61 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+pub mod forest;
+use forest::Forest;
 
-fn main() {
-    {
-        // Part 1
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut num_trees = 0;
-        let mut posx = 0;
-        // let mut vec = Vec::new();
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            // println!("line: {}", line);
+const SLOPES: [(isize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
 
-            if line.chars().nth(posx).unwrap() == '#' {
-                num_trees += 1;
-            }
-
-            posx += 3;
+fn product_of_trees(forest: &Forest) -> usize {
+    SLOPES
+        .iter()
+        .map(|(right, down)| forest.trees_on_slope(*right, *down).unwrap())
+        .product()
+}
 
-            if posx >= line.chars().count() {
-                posx -= line.chars().count();
-            }
-            // vec.push(line);
-        }
-        println!("Part 1: Num trees: {}", num_trees);
-    }
+// Usage: day3 [--render]
+fn main() {
     {
-        // Part 2
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut num_trees: [usize; 5] = [0; 5];
-        let mut posx: [usize; 5] = [0; 5];
-        let mut right_slope: [usize; 5] = [1, 3, 5, 7, 0];
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            // println!("line: {}", line);
+        // Tests
+        let forest = Forest::from_file("test1.txt").unwrap();
+        assert_eq!((forest.width(), forest.height()), (11, 11));
+        let trees: Vec<usize> = SLOPES
+            .iter()
+            .map(|(right, down)| forest.trees_on_slope(*right, *down).unwrap())
+            .collect();
+        assert_eq!(trees, vec![2, 7, 3, 4, 2]);
+        assert_eq!(product_of_trees(&forest), 336);
+        assert_eq!(forest.trees_on_slope(-3, 1), forest.trees_on_slope(8, 1)); // 11 - 3
+        assert_eq!(forest.trees_on_slope(0, 20), Ok(0)); // Only the top left
+        assert!(forest.trees_on_slope(1, 0).is_err());
+        assert!(forest.is_tree(-1, 3)); // The last column of the row
+        let (_, fewest) = forest.best_slope(5, 3).unwrap();
+        assert_eq!(fewest, 0);
 
-            for i in 0..5 {
-                if i != 4 {
-                    if line.chars().nth(posx[i]).unwrap() == '#' {
-                        num_trees[i] += 1;
-                    }
-                } else {
-                    if right_slope[i] == 0 {
-                        if line.chars().nth(posx[i]).unwrap() == '#' {
-                            num_trees[i] += 1;
-                        }
-                        right_slope[i] = 1;
-                    } else {
-                        right_slope[i] = 0;
-                    }
-                }
-
-                posx[i] += right_slope[i];
+        let render = forest.render(3, 1).unwrap();
+        let lines: Vec<&str> = render.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(
+            lines[0],
+            "O.##.......".to_string() + &"..##.......".repeat(2)
+        ); // x goes up to 30
+        assert_eq!(&lines[1][..11], "#..O#...#..");
+        assert_eq!(&lines[2][6..11], "X..#.");
+        assert_eq!(render.matches('X').count(), 7);
+        assert!(Forest::parse(&["#.".to_string(), "#".to_string()]).is_err());
+        assert!(Forest::parse(&["#o".to_string()]).is_err());
+        assert!(Forest::parse(&[]).is_err());
+    }
 
-                if posx[i] >= line.chars().count() {
-                    posx[i] -= line.chars().count();
-                }
-            }
-        }
-        println!(
-            "Part 2: Num trees: {}",
-            num_trees[0] * num_trees[1] * num_trees[2] * num_trees[3] * num_trees[4]
-        );
+    let forest = Forest::from_file("input.txt").unwrap();
+    if std::env::args().any(|arg| arg == "--render") {
+        print!("{}", forest.render(3, 1).unwrap());
     }
+    println!(
+        "Part 1: Num trees: {}",
+        forest.trees_on_slope(3, 1).unwrap()
+    );
+    println!("Part 2: Num trees: {}", product_of_trees(&forest));
+    let ((right, down), trees) = forest.best_slope(7, 2).unwrap();
+    println!(
+        "Fewest trees: {} going right {} down {}",
+        trees, right, down
+    );
 }
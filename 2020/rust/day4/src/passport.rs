@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+
+// One batch of `key:value' pairs, records are separated by blank lines
+#[derive(Clone, PartialEq, Debug)]
+pub struct Record {
+    pub line: usize, // First line of the record, counting from 1
+    pub fields: BTreeMap<String, String>,
+}
+
+pub fn parse_records(text: &str) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    let mut current: Option<Record> = None;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            records.extend(current.take());
+            continue;
+        }
+        let record = current.get_or_insert_with(|| Record {
+            line: i + 1,
+            fields: BTreeMap::new(),
+        });
+        for entry in line.split_whitespace() {
+            let mut splits = entry.splitn(2, ':');
+            let (key, value) = match (splits.next(), splits.next()) {
+                (Some(key), Some(value)) if !key.is_empty() => (key, value),
+                _ => {
+                    return Err(format!(
+                        "Line {}: expected `key:value', got `{}'",
+                        i + 1,
+                        entry
+                    ))
+                }
+            };
+            if record
+                .fields
+                .insert(key.to_string(), value.to_string())
+                .is_some()
+            {
+                return Err(format!("Line {}: `{}' given twice", i + 1, key));
+            }
+        }
+    }
+    records.extend(current);
+    Ok(records)
+}
+
+pub fn records_from_file(file: &str) -> Result<Vec<Record>, String> {
+    parse_records(&fs::read_to_string(file).map_err(|e| format!("Unable to read {}: {}", file, e))?)
+}
+
+// What a value has to look like
+#[derive(Clone, PartialEq, Debug)]
+pub enum Rule {
+    Year(RangeInclusive<u32>), // Four digits in the range
+    Measure(&'static [(&'static str, RangeInclusive<u32>)]), // A number and a unit, in the range of the unit
+    HexColour,                                               // `#' and six lowercase hex digits
+    OneOf(&'static [&'static str]),
+    Digits(usize), // Exactly this many digits, leading zeroes included
+    Any,
+}
+
+fn number_in(s: &str, range: &RangeInclusive<u32>) -> Result<(), String> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("`{}' is not a number", s));
+    }
+    match s.parse::<u32>() {
+        Ok(n) if range.contains(&n) => Ok(()),
+        _ => Err(format!(
+            "{} is not in {}..={}",
+            s,
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+impl Rule {
+    // Ok, or why not
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Rule::Year(range) => {
+                if value.len() != 4 {
+                    return Err(format!("`{}' is not a four digit year", value));
+                }
+                number_in(value, range)
+            }
+            Rule::Measure(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (number, unit) = value.split_at(split);
+                match units.iter().find(|(name, _)| *name == unit) {
+                    Some((_, range)) => number_in(number, range),
+                    None => {
+                        let names: Vec<&str> = units.iter().map(|(name, _)| *name).collect();
+                        Err(format!("`{}' is not in {}", value, names.join(" or ")))
+                    }
+                }
+            }
+            Rule::HexColour => {
+                let ok = value.len() == 7
+                    && value.starts_with('#')
+                    && value[1..]
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+                if ok {
+                    Ok(())
+                } else {
+                    Err(format!("`{}' is not `#' and six hex digits", value))
+                }
+            }
+            Rule::OneOf(options) => {
+                if options.contains(&value) {
+                    Ok(())
+                } else {
+                    Err(format!("`{}' is not one of {}", value, options.join(", ")))
+                }
+            }
+            Rule::Digits(n) => {
+                if value.len() == *n && value.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(())
+                } else {
+                    Err(format!("`{}' is not {} digits", value, n))
+                }
+            }
+            Rule::Any => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Field {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+pub static PASSPORT: [Field; 8] = [
+    Field {
+        key: "byr",
+        name: "Birth Year",
+        required: true,
+        rule: Rule::Year(1920..=2002),
+    },
+    Field {
+        key: "iyr",
+        name: "Issue Year",
+        required: true,
+        rule: Rule::Year(2010..=2020),
+    },
+    Field {
+        key: "eyr",
+        name: "Expiration Year",
+        required: true,
+        rule: Rule::Year(2020..=2030),
+    },
+    Field {
+        key: "hgt",
+        name: "Height",
+        required: true,
+        rule: Rule::Measure(&[("cm", 150..=193), ("in", 59..=76)]),
+    },
+    Field {
+        key: "hcl",
+        name: "Hair Color",
+        required: true,
+        rule: Rule::HexColour,
+    },
+    Field {
+        key: "ecl",
+        name: "Eye Color",
+        required: true,
+        rule: Rule::OneOf(&["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]),
+    },
+    Field {
+        key: "pid",
+        name: "Passport ID",
+        required: true,
+        rule: Rule::Digits(9),
+    },
+    Field {
+        key: "cid",
+        name: "Country ID",
+        required: false,
+        rule: Rule::Any,
+    },
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Problem {
+    Missing(&'static Field),
+    Invalid {
+        field: &'static Field,
+        value: String,
+        reason: String,
+    },
+    Unknown(String), // A key the schema does not have
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Missing(field) => write!(f, "missing {} ({})", field.key, field.name),
+            Problem::Invalid { field, reason, .. } => write!(f, "bad {}: {}", field.key, reason),
+            Problem::Unknown(key) => write!(f, "unknown field {}", key),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Check {
+    Presence, // Only that the required fields are there
+    Values,   // Also that every value follows its rule
+}
+
+// Everything wrong with `record' under `schema', empty if it is valid
+pub fn problems(schema: &'static [Field], record: &Record, check: Check) -> Vec<Problem> {
+    let mut out = Vec::new();
+    for field in schema {
+        match record.fields.get(field.key) {
+            None if field.required => out.push(Problem::Missing(field)),
+            Some(value) if check == Check::Values => {
+                if let Err(reason) = field.rule.check(value) {
+                    out.push(Problem::Invalid {
+                        field,
+                        value: value.clone(),
+                        reason,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    for key in record.fields.keys() {
+        if schema.iter().all(|field| field.key != key) {
+            out.push(Problem::Unknown(key.clone()));
+        }
+    }
+    out
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeSet;
+
+// A row and a column, both counted from 0 at the front and the left
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BoardingPass {
+    pub row: usize,
+    pub col: usize,
+}
+
+// How many binary partitioning steps pick the row (F/B) and the column (L/R)
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Layout {
+    pub row_bits: u32,
+    pub col_bits: u32,
+}
+
+pub const AIRCRAFT: Layout = Layout {
+    row_bits: 7,
+    col_bits: 3,
+};
+
+// Every letter halves the range, the lower half for `low' and the upper half for `high'
+fn decode_bits(s: &str, low: char, high: char) -> Result<usize, String> {
+    s.chars().try_fold(0, |acc, c| match c {
+        c if c == low => Ok(acc << 1),
+        c if c == high => Ok(acc << 1 | 1),
+        _ => Err(format!("Expected {} or {}, got `{}'", low, high, c)),
+    })
+}
+
+fn encode_bits(value: usize, bits: u32, low: char, high: char) -> String {
+    (0..bits)
+        .rev()
+        .map(|bit| if value >> bit & 1 == 0 { low } else { high })
+        .collect()
+}
+
+impl Layout {
+    pub fn rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    pub fn cols(&self) -> usize {
+        1 << self.col_bits
+    }
+
+    // Parses `FBFBBFFRLR'
+    pub fn decode(&self, s: &str) -> Result<BoardingPass, String> {
+        let s = s.trim();
+        if s.len() != (self.row_bits + self.col_bits) as usize || !s.is_ascii() {
+            return Err(format!(
+                "Expected {} row and {} column letters, got `{}'",
+                self.row_bits, self.col_bits, s
+            ));
+        }
+        let (row, col) = s.split_at(self.row_bits as usize);
+        Ok(BoardingPass {
+            row: decode_bits(row, 'F', 'B')?,
+            col: decode_bits(col, 'L', 'R')?,
+        })
+    }
+
+    pub fn encode(&self, pass: &BoardingPass) -> String {
+        assert!(
+            pass.row < self.rows() && pass.col < self.cols(),
+            "{:?} is not on board",
+            pass
+        );
+        encode_bits(pass.row, self.row_bits, 'F', 'B')
+            + &encode_bits(pass.col, self.col_bits, 'L', 'R')
+    }
+
+    // The row times the number of columns plus the column, which is the letters read as binary
+    pub fn seat_id(&self, pass: &BoardingPass) -> usize {
+        pass.row << self.col_bits | pass.col
+    }
+
+    pub fn from_seat_id(&self, id: usize) -> BoardingPass {
+        BoardingPass {
+            row: id >> self.col_bits,
+            col: id & (self.cols() - 1),
+        }
+    }
+
+    // Free seats with a taken seat on either side by seat ID, which is where a missing
+    // passenger can be
+    pub fn gaps(&self, passes: &[BoardingPass]) -> Vec<BoardingPass> {
+        let taken: BTreeSet<usize> = passes.iter().map(|pass| self.seat_id(pass)).collect();
+        taken
+            .iter()
+            .filter(|id| !taken.contains(&(*id + 1)) && taken.contains(&(*id + 2)))
+            .map(|id| self.from_seat_id(id + 1))
+            .collect()
+    }
+
+    // The aircraft from the front, `#' is taken, `.' is free and `O' is a gap. The aisle is
+    // between the middle columns.
+    pub fn seat_map(&self, passes: &[BoardingPass]) -> String {
+        let taken: BTreeSet<&BoardingPass> = passes.iter().collect();
+        let gaps: BTreeSet<BoardingPass> = self.gaps(passes).into_iter().collect();
+        let label = format!("{}", self.rows() - 1).len();
+        let mut out = String::new();
+        for row in 0..self.rows() {
+            out += &format!("{:>1$} ", row, label);
+            for col in 0..self.cols() {
+                if col == self.cols() / 2 && self.cols() > 1 {
+                    out.push(' ');
+                }
+                let pass = BoardingPass { row, col };
+                out.push(if taken.contains(&pass) {
+                    '#'
+                } else if gaps.contains(&pass) {
+                    'O'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::string::string_regex;
+
+    fn layout_and_code() -> impl Strategy<Value = (Layout, String)> {
+        (0u32..12, 0u32..6).prop_flat_map(|(row_bits, col_bits)| {
+            let pattern = format!("[FB]{{{}}}[LR]{{{}}}", row_bits, col_bits);
+            (
+                Just(Layout { row_bits, col_bits }),
+                string_regex(&pattern).unwrap(),
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn encode_after_decode_is_identity((layout, code) in layout_and_code()) {
+            let pass = layout.decode(&code).unwrap();
+            prop_assert_eq!(layout.encode(&pass), code);
+        }
+
+        #[test]
+        fn seat_id_round_trips((layout, code) in layout_and_code()) {
+            let pass = layout.decode(&code).unwrap();
+            prop_assert_eq!(layout.from_seat_id(layout.seat_id(&pass)), pass);
+        }
+    }
+
+    #[test]
+    fn test_gaps() {
+        let passes: Vec<BoardingPass> = [3, 4, 6, 7, 9, 12, 13]
+            .iter()
+            .map(|id| AIRCRAFT.from_seat_id(*id))
+            .collect();
+        let gaps: Vec<usize> = AIRCRAFT
+            .gaps(&passes)
+            .iter()
+            .map(|p| AIRCRAFT.seat_id(p))
+            .collect();
+        assert_eq!(gaps, vec![5, 8]);
+    }
+}
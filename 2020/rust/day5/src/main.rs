@@ -1,57 +1,69 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+pub mod boarding;
+use boarding::{BoardingPass, Layout, AIRCRAFT};
+
 // F = 0
 // B = 1
 // L = 0
 // R = 1
 
 fn get_seat_id(s: &str) -> usize {
-    s.chars()
-        .fold((0, 2_usize.pow(9)), |(acc, mul), c| {
-            // println!("folding: {} @ {:?}", c, (acc, mul));
-            (
-                acc + (mul
-                    * (match c {
-                        'F' | 'L' => 0,
-                        _ => 1,
-                    } as usize)),
-                mul >> 1,
-            )
-        })
-        .0
+    AIRCRAFT.seat_id(&AIRCRAFT.decode(s).expect("Unable to decode boarding pass"))
 }
 
+// Usage: day5 [--map]
 fn main() {
     assert_eq!(get_seat_id("BFFFBBFRRR"), 567);
     assert_eq!(get_seat_id("FFFBBBFRRR"), 119);
     assert_eq!(get_seat_id("BBFFBBFRLL"), 820);
+    assert_eq!(
+        AIRCRAFT.decode("FBFBBFFRLR"),
+        Ok(BoardingPass { row: 44, col: 5 })
+    );
+    assert_eq!(
+        AIRCRAFT.encode(&BoardingPass { row: 70, col: 7 }),
+        "BFFFBBFRRR"
+    );
+    assert!(AIRCRAFT.decode("FBFBBFFRL").is_err());
+    assert!(AIRCRAFT.decode("FBFBBFFRLX").is_err());
+    let small = Layout {
+        row_bits: 2,
+        col_bits: 1,
+    };
+    assert_eq!(small.decode("BFR"), Ok(BoardingPass { row: 2, col: 1 }));
+    let passes: Vec<BoardingPass> = [0, 1, 3, 6, 7]
+        .iter()
+        .map(|id| small.from_seat_id(*id))
+        .collect();
+    assert_eq!(small.gaps(&passes), vec![BoardingPass { row: 1, col: 0 }]);
+    assert_eq!(small.seat_map(&passes), "0 # #\n1 O #\n2 . .\n3 # #\n");
 
     {
         // Part 1
         let f = File::open("input.txt").expect("Unable to open file");
         let f = BufReader::new(f);
-        let mut v = Vec::new();
+        let mut passes = Vec::new();
         for line in f.lines() {
             let line = line.expect("Unable to read line");
-            // println!("{}", line);
-            v.push(get_seat_id(&line));
+            passes.push(
+                AIRCRAFT
+                    .decode(&line)
+                    .expect("Unable to decode boarding pass"),
+            );
+        }
+        if std::env::args().any(|arg| arg == "--map") {
+            print!("{}", AIRCRAFT.seat_map(&passes));
         }
-        v.sort();
-        println!("Part 1: Highest seat: {}", v.last().unwrap());
-        println!(
-            "Part 2: My seat: {}",
-            v.iter()
-                .cloned()
-                .fold((0, 0, 0), |(p1, p2, ans), t| {
-                    // println!("folding: {} @ {:?}", t, (p1, p2, ans));
-                    if p1 + 1 == p2 && p2 + 2 == t {
-                        (p2, t, t - 1)
-                    } else {
-                        (p2, t, ans)
-                    }
-                })
-                .2
+        let highest = passes.iter().map(|pass| AIRCRAFT.seat_id(pass)).max();
+        println!("Part 1: Highest seat: {}", highest.unwrap());
+        let gaps = AIRCRAFT.gaps(&passes);
+        assert_eq!(
+            gaps.len(),
+            1,
+            "Expected exactly one free seat between taken ones"
         );
+        println!("Part 2: My seat: {}", AIRCRAFT.seat_id(&gaps[0]));
     }
 }
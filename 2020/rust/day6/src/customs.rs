@@ -0,0 +1,175 @@
+use std::fmt;
+use std::io::BufRead;
+use std::ops::{BitAnd, BitOr, BitXor};
+
+// Lines with a blank line after every group, the last one may be missing
+pub fn read_groups<R: BufRead>(reader: R) -> Result<Vec<Vec<String>>, String> {
+    let mut groups = Vec::new();
+    let mut group = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Unable to read line {}: {}", i + 1, e))?;
+        let line = line.trim();
+        if line.is_empty() {
+            if !group.is_empty() {
+                groups.push(group);
+                group = Vec::new();
+            }
+        } else {
+            group.push(line.to_string());
+        }
+    }
+    if !group.is_empty() {
+        groups.push(group);
+    }
+    Ok(groups)
+}
+
+// The questions one person answered yes to, one bit per question in the order of `Questions'
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Answers(u64);
+
+impl Answers {
+    pub const NONE: Answers = Answers(0);
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, question: usize) -> bool {
+        question < 64 && self.0 >> question & 1 == 1
+    }
+}
+
+impl BitOr for Answers {
+    type Output = Answers;
+    fn bitor(self, other: Answers) -> Answers {
+        Answers(self.0 | other.0)
+    }
+}
+
+impl BitAnd for Answers {
+    type Output = Answers;
+    fn bitand(self, other: Answers) -> Answers {
+        Answers(self.0 & other.0)
+    }
+}
+
+impl BitXor for Answers {
+    type Output = Answers;
+    fn bitxor(self, other: Answers) -> Answers {
+        Answers(self.0 ^ other.0)
+    }
+}
+
+// The letters the questions are asked with, at most 64 of them
+#[derive(Clone, PartialEq, Debug)]
+pub struct Questions {
+    letters: Vec<char>,
+}
+
+impl Questions {
+    pub fn new(letters: &str) -> Result<Questions, String> {
+        let letters: Vec<char> = letters.chars().collect();
+        if letters.len() > 64 {
+            return Err(format!("{} questions, at most 64 fit", letters.len()));
+        }
+        for (i, c) in letters.iter().enumerate() {
+            if letters[..i].contains(c) {
+                return Err(format!("Question {} is there twice", c));
+            }
+        }
+        Ok(Questions { letters })
+    }
+
+    // The puzzle's 26 questions, `a' to `z'
+    pub fn puzzle() -> Questions {
+        Questions::new("abcdefghijklmnopqrstuvwxyz").unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    // Every question answered yes
+    pub fn all(&self) -> Answers {
+        Answers(if self.len() == 64 {
+            !0
+        } else {
+            (1 << self.len()) - 1
+        })
+    }
+
+    // `abx', the questions one person answered yes to
+    pub fn parse(&self, line: &str) -> Result<Answers, String> {
+        line.trim().chars().try_fold(Answers::NONE, |acc, c| {
+            match self.letters.iter().position(|q| *q == c) {
+                Some(question) => Ok(acc | Answers(1 << question)),
+                None => Err(format!("No question `{}' in `{}'", c, line)),
+            }
+        })
+    }
+
+    pub fn parse_group(&self, group: &[String]) -> Result<Vec<Answers>, String> {
+        group.iter().map(|line| self.parse(line)).collect()
+    }
+
+    pub fn letters(&self, answers: Answers) -> String {
+        self.letters
+            .iter()
+            .enumerate()
+            .filter(|(question, _)| answers.contains(*question))
+            .map(|(_, c)| c)
+            .collect()
+    }
+}
+
+impl fmt::Display for Questions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.letters.iter().collect::<String>())
+    }
+}
+
+// How the answers of everyone in a group make up the group's answers
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Combinator {
+    Anyone,         // Union
+    Everyone,       // Intersection
+    Odd,            // Symmetric difference, answered by an odd number of people
+    AtLeast(usize), // Answered by at least this many people
+}
+
+impl Combinator {
+    pub fn combine(self, questions: &Questions, group: &[Answers]) -> Answers {
+        match self {
+            Combinator::Anyone => group.iter().fold(Answers::NONE, |acc, a| acc | *a),
+            Combinator::Everyone => group.iter().fold(questions.all(), |acc, a| acc & *a),
+            Combinator::Odd => group.iter().fold(Answers::NONE, |acc, a| acc ^ *a),
+            Combinator::AtLeast(k) => (0..questions.len())
+                .filter(|question| group.iter().filter(|a| a.contains(*question)).count() >= k)
+                .fold(Answers::NONE, |acc, question| acc | Answers(1 << question)),
+        }
+    }
+}
+
+// The sum over every group of how many questions the group answered yes to
+pub fn count(
+    questions: &Questions,
+    groups: &[Vec<String>],
+    combinator: Combinator,
+) -> Result<usize, String> {
+    let mut sum = 0;
+    for group in groups {
+        sum += combinator
+            .combine(questions, &questions.parse_group(group)?)
+            .len();
+    }
+    Ok(sum)
+}
@@ -1,61 +1,49 @@
-use std::collections::BTreeSet;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
+
+pub mod customs;
+use customs::{count, read_groups, Combinator, Questions};
 
 fn main() {
     {
-        // Part 1
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut count = 0;
-        let mut set = BTreeSet::new();
-        for line in f.lines() {
-            let line = line.expect("Unable to read line");
-            // println!("{}", line);
-            if line.len() == 0 {
-                // println!("Finished set. adding {:?} (len = {})", set, set.len());
-                count += set.len();
-                // println!("New count: {}", count);
-                set.clear();
-            } else {
-                line.chars().for_each(|c| {
-                    set.insert(c);
-                })
-            }
-        }
-        println!("Part 1: {}", count);
+        // Tests
+        let groups = read_groups("abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb".as_bytes()).unwrap();
+        assert_eq!(groups.len(), 5); // No blank line after the last one
+        let questions = Questions::puzzle();
+        assert_eq!(count(&questions, &groups, Combinator::Anyone), Ok(11));
+        assert_eq!(count(&questions, &groups, Combinator::Everyone), Ok(6));
+        assert_eq!(count(&questions, &groups, Combinator::AtLeast(1)), Ok(11));
+        assert_eq!(count(&questions, &groups, Combinator::AtLeast(2)), Ok(2));
+        assert_eq!(count(&questions, &groups, Combinator::Odd), Ok(9));
+
+        let group = questions
+            .parse_group(&["abcx".to_string(), "abcy".to_string(), "abcz".to_string()])
+            .unwrap();
+        let letters =
+            |combinator: Combinator| questions.letters(combinator.combine(&questions, &group));
+        assert_eq!(letters(Combinator::Anyone), "abcxyz");
+        assert_eq!(letters(Combinator::Everyone), "abc");
+        assert_eq!(letters(Combinator::Odd), "abcxyz"); // abc three times
+        assert_eq!(letters(Combinator::AtLeast(2)), "abc");
+        assert!(questions.parse("aB").is_err());
+
+        let larger =
+            Questions::new("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789")
+                .unwrap();
+        assert_eq!(larger.len(), 62);
+        assert_eq!(larger.parse("aB9").unwrap().len(), 3);
+        assert_eq!(larger.letters(larger.all()), larger.to_string());
+        assert!(Questions::new("aa").is_err());
     }
-    {
-        // Part 2
-        let f = File::open("input.txt").expect("Unable to open file");
-        let f = BufReader::new(f);
-        let mut count = 0;
-        let mut set = BTreeSet::new();
-        let mut people = 0;
-        for line in f.lines() {
-            let mut newset = BTreeSet::new();
-            let line = line.expect("Unable to read line");
-            // println!("{}", line);
-            if line.len() == 0 {
-                // println!("Finished set. adding {:?} (len = {})", set, set.len());
-                count += set.len();
-                // println!("New count: {}\n===", count);
-                set.clear();
-                people = 0;
-            } else {
-                line.chars().for_each(|c| {
-                    newset.insert(c);
-                });
-                if people == 0 {
-                    set = newset;
-                // println!("Initial: {:?}", set);
-                } else {
-                    set = newset.intersection(&set).cloned().collect();
-                    // println!("post intersection: {:?}", set);
-                }
-                people += 1;
-            }
-        }
-        println!("Part 2: {}", count);
+
+    let f = File::open("input.txt").expect("Unable to open file");
+    let groups = read_groups(BufReader::new(f)).unwrap();
+    let questions = Questions::puzzle();
+    for (part, combinator) in [(1, Combinator::Anyone), (2, Combinator::Everyone)].iter() {
+        println!(
+            "Part {}: {}",
+            part,
+            count(&questions, &groups, *combinator).unwrap()
+        );
     }
 }
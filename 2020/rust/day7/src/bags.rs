@@ -0,0 +1,221 @@
+use num_bigint::BigUint;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::rc::Rc;
+
+// Which bags go directly inside which, checked to have no cycles when parsed. Answers are memoised
+// per colour, so asking about every colour never redoes the work for the bags below or above it.
+#[derive(Debug)]
+pub struct BagRules {
+    names: Vec<String>,
+    index: HashMap<String, usize>, // Colour -> position in `names'
+    contents: Vec<Vec<(BigUint, usize)>>, // Direct contents of every colour, (count, colour)
+    parents: Vec<Vec<usize>>,      // Colours that directly contain every colour
+    ancestors: RefCell<Vec<Option<Rc<BTreeSet<usize>>>>>,
+    totals: RefCell<Vec<Option<BigUint>>>,
+}
+
+// `shiny gold' from `shiny gold bags', `1 shiny gold bag' has the count removed first
+fn colour_of(s: &str) -> Option<&str> {
+    let s = s.trim().trim_end_matches('.');
+    let colour = s
+        .strip_suffix(" bags")
+        .or_else(|| s.strip_suffix(" bag"))?
+        .trim();
+    if colour.is_empty() {
+        None
+    } else {
+        Some(colour)
+    }
+}
+
+impl BagRules {
+    // One `light red bags contain 1 bright white bag, 22 muted yellow bags.' per line
+    pub fn parse(lines: &[String]) -> Result<BagRules, String> {
+        let mut names: Vec<String> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut rules: Vec<Option<Vec<(BigUint, usize)>>> = Vec::new(); // None until its line is read
+        let mut id = |name: &str, names: &mut Vec<String>| {
+            *index.entry(name.to_string()).or_insert_with(|| {
+                names.push(name.to_string());
+                names.len() - 1
+            })
+        };
+        for line in lines.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let bad = || {
+                format!(
+                    "Expected `<colour> bags contain <contents>.', got `{}'",
+                    line
+                )
+            };
+            let mut splits = line.splitn(2, " contain ");
+            let (outer, inner) = match (splits.next(), splits.next()) {
+                (Some(outer), Some(inner)) => (outer, inner.trim()),
+                _ => return Err(bad()),
+            };
+            let outer = id(colour_of(outer).ok_or_else(bad)?, &mut names);
+            let mut contents = Vec::new();
+            if inner.trim_end_matches('.') != "no other bags" {
+                for item in inner.split(',') {
+                    let item = item.trim();
+                    let split = item.find(' ').ok_or_else(bad)?;
+                    let (count, colour) = item.split_at(split);
+                    let count = count
+                        .parse::<BigUint>()
+                        .map_err(|_| format!("Bad count `{}' in `{}'", count, line))?;
+                    let colour = id(colour_of(colour).ok_or_else(bad)?, &mut names);
+                    contents.push((count, colour));
+                }
+            }
+            rules.resize(names.len(), None);
+            if rules[outer].is_some() {
+                return Err(format!("Two rules for {} bags", names[outer]));
+            }
+            rules[outer] = Some(contents);
+        }
+        rules.resize(names.len(), None);
+        let contents = rules
+            .into_iter()
+            .enumerate()
+            .map(|(v, rule)| rule.ok_or(format!("No rule for {} bags", names[v])))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut parents = vec![Vec::new(); names.len()];
+        for (v, inner) in contents.iter().enumerate() {
+            for (_, child) in inner {
+                parents[*child].push(v);
+            }
+        }
+
+        let n = names.len();
+        let rules = BagRules {
+            names,
+            index,
+            contents,
+            parents,
+            ancestors: RefCell::new(vec![None; n]),
+            totals: RefCell::new(vec![None; n]),
+        };
+        if let Some(cycle) = rules.find_cycle() {
+            let cycle: Vec<&str> = cycle.iter().map(|v| rules.names[*v].as_str()).collect();
+            return Err(format!("Bags contain themselves: {}", cycle.join(" -> ")));
+        }
+        Ok(rules)
+    }
+
+    pub fn from_file(file: &str) -> Result<BagRules, String> {
+        let f = File::open(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        let lines: Result<Vec<String>, _> = BufReader::new(f).lines().collect();
+        BagRules::parse(&lines.map_err(|e| format!("Unable to read {}: {}", file, e))?)
+    }
+
+    // Depth first search keeping the current path, the first colour reached twice on it closes a
+    // cycle. Returned from that colour back to itself.
+    fn find_cycle(&self) -> Option<Vec<usize>> {
+        #[derive(Copy, Clone, PartialEq)]
+        enum State {
+            New,
+            OnPath,
+            Done,
+        }
+        let mut state = vec![State::New; self.names.len()];
+        for start in 0..self.names.len() {
+            if state[start] != State::New {
+                continue;
+            }
+            let mut path: Vec<(usize, usize)> = vec![(start, 0)]; // (colour, next content to visit)
+            state[start] = State::OnPath;
+            while let Some((v, next)) = path.last_mut() {
+                match self.contents[*v].get(*next) {
+                    Some((_, child)) => {
+                        *next += 1;
+                        match state[*child] {
+                            State::New => {
+                                state[*child] = State::OnPath;
+                                path.push((*child, 0));
+                            }
+                            State::OnPath => {
+                                let from = path.iter().position(|(u, _)| u == child).unwrap();
+                                let mut cycle: Vec<usize> =
+                                    path[from..].iter().map(|(u, _)| *u).collect();
+                                cycle.push(*child);
+                                return Some(cycle);
+                            }
+                            State::Done => {}
+                        }
+                    }
+                    None => {
+                        state[*v] = State::Done;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn id(&self, colour: &str) -> Result<usize, String> {
+        self.index
+            .get(colour)
+            .copied()
+            .ok_or(format!("No rule for {} bags", colour))
+    }
+
+    pub fn colours(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_str())
+    }
+
+    fn ancestor_ids(&self, v: usize) -> Rc<BTreeSet<usize>> {
+        if let Some(known) = &self.ancestors.borrow()[v] {
+            return known.clone();
+        }
+        let mut out = BTreeSet::new();
+        for parent in &self.parents[v] {
+            out.insert(*parent);
+            out.extend(self.ancestor_ids(*parent).iter());
+        }
+        let out = Rc::new(out);
+        self.ancestors.borrow_mut()[v] = Some(out.clone());
+        out
+    }
+
+    // Every colour that eventually holds a `colour' bag
+    pub fn ancestors_of(&self, colour: &str) -> Result<BTreeSet<&str>, String> {
+        let ancestors = self.ancestor_ids(self.id(colour)?);
+        Ok(ancestors.iter().map(|v| self.names[*v].as_str()).collect())
+    }
+
+    fn total_id(&self, v: usize) -> BigUint {
+        if let Some(known) = &self.totals.borrow()[v] {
+            return known.clone();
+        }
+        let mut out = BigUint::from(0u32);
+        for (count, child) in &self.contents[v] {
+            out += count * (self.total_id(*child) + 1u32);
+        }
+        self.totals.borrow_mut()[v] = Some(out.clone());
+        out
+    }
+
+    // Number of bags inside a `colour' bag, at any depth
+    pub fn total_contents(&self, colour: &str) -> Result<BigUint, String> {
+        Ok(self.total_id(self.id(colour)?))
+    }
+
+    // Graphviz format, edges point from a bag to what it holds
+    pub fn to_dot(&self) -> String {
+        let mut out = "digraph bags {\n".to_string();
+        for (v, inner) in self.contents.iter().enumerate() {
+            for (count, child) in inner {
+                out += &format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    self.names[v], self.names[*child], count
+                );
+            }
+        }
+        out += "}\n";
+        out
+    }
+}
@@ -1,94 +1,94 @@
-use regex::Regex;
-use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+pub mod bags;
+use bags::BagRules;
+use num_bigint::BigUint;
 
-// recursive stuff
-fn contains_my_bag(
-    my_bag: &str,
-    target: &str,
-    map: &BTreeMap<String, Vec<(usize, String)>>,
-) -> bool {
-    let mut out = false;
-    // println!("Looking for {}", target);
-    for (_, t) in map.get(target).unwrap().iter() {
-        if t == my_bag || contains_my_bag(my_bag, t, map) {
-            out = true;
-            break;
-        }
-    }
-    out
-}
-
-fn count_sub_bags(
-    target: &str,
-    map: &BTreeMap<String, Vec<(usize, String)>>,
-    indent: usize,
-) -> usize {
-    let mut out = 0;
-    // let indent_str = (0..indent * 2).map(|_| " ").collect::<String>();
-    // println!("{}Counting bags inside {} bags", indent_str, target);
-    for (num, t) in map.get(target).unwrap().iter() {
-        // println!("{}Found {} {} bags", indent_str, num, t);
-        out += num + num * count_sub_bags(t, map, indent + 1);
-    }
-    // println!("{}= New total: {}", indent_str, out);
-    out
+fn lines(s: &str) -> Vec<String> {
+    s.lines().map(|l| l.to_string()).collect()
 }
 
+// Usage: day7 [--dot]
 fn main() {
-    let re_lhs = Regex::new(r"^([a-z]+ [a-z]+)").unwrap();
-    let re_rhs = Regex::new(r"([0-9]) ([a-z]+ [a-z]+)").unwrap();
     {
         // Asserts
-        let s = "dull silver bags contain 2 striped magenta bags, 2 dark coral bags, 1 bright orange bag, 4 plaid blue bags.";
-        let goals = vec![
-            "dull silver",
-            "2",
-            "striped magenta",
-            "2",
-            "dark coral",
-            "1",
-            "bright orange",
-            "4",
-            "plaid blue",
-        ];
-        let caps = re_lhs.captures(s).unwrap();
-        assert_eq!(&caps[1], goals[0]);
-        for (i, caps) in re_rhs.captures_iter(s).enumerate() {
-            assert_eq!(&caps[1], goals[(2 * i) + 1]);
-            assert_eq!(&caps[2], goals[(2 * i) + 2]);
-        }
-    }
-
-    let f = File::open("input.txt").expect("Unable to open file");
-    let f = BufReader::new(f);
-    let mut map = BTreeMap::new();
+        let rules = BagRules::from_file("test.txt").unwrap();
+        let ancestors = rules.ancestors_of("shiny gold").unwrap();
+        let expected = ["bright white", "dark orange", "light red", "muted yellow"];
+        assert_eq!(ancestors.into_iter().collect::<Vec<_>>(), expected);
+        assert_eq!(rules.total_contents("shiny gold"), Ok(BigUint::from(32u32)));
+        assert_eq!(rules.total_contents("faded blue"), Ok(BigUint::from(0u32)));
+        assert!(rules.ancestors_of("light red").unwrap().is_empty());
+        assert!(rules.total_contents("plaid green").is_err());
 
-    for line in f.lines() {
-        let line = line.expect("Unable to read line");
-        let caps = re_lhs.captures(&line).unwrap();
+        let rules = BagRules::parse(&lines(
+            "shiny gold bags contain 2 dark red bags.
+             dark red bags contain 2 dark orange bags.
+             dark orange bags contain 2 dark yellow bags.
+             dark yellow bags contain 2 dark green bags.
+             dark green bags contain 2 dark blue bags.
+             dark blue bags contain 2 dark violet bags.
+             dark violet bags contain no other bags.",
+        ))
+        .unwrap();
+        assert_eq!(
+            rules.total_contents("shiny gold"),
+            Ok(BigUint::from(126u32))
+        );
 
-        let mut v = Vec::new();
-        for caps in re_rhs.captures_iter(&line) {
-            v.push((caps[1].parse::<usize>().unwrap(), caps[2].to_string()));
-        }
+        // Counts with several digits, and nesting that overflows 64 bits
+        let rules = BagRules::parse(&lines(
+            "dull silver bags contain 12 striped magenta bags, 2 dark coral bags, 1 bright orange bag.
+             striped magenta bags contain no other bags.
+             dark coral bags contain no other bags.
+             bright orange bags contain 100 dark coral bags.",
+        ))
+        .unwrap();
+        assert_eq!(
+            rules.total_contents("dull silver"),
+            Ok(BigUint::from(115u32))
+        );
+        let depth = 30;
+        let chain: Vec<String> = (0..depth)
+            .map(|i| format!("level {} bags contain 1000 level {} bags.", i, i + 1))
+            .chain(std::iter::once(format!(
+                "level {} bags contain no other bags.",
+                depth
+            )))
+            .collect();
+        let rules = BagRules::parse(&chain).unwrap();
+        let expected: BigUint = (1..=depth).map(|i| BigUint::from(1000u32).pow(i)).sum();
+        assert_eq!(rules.total_contents("level 0"), Ok(expected));
+        assert_eq!(
+            rules
+                .ancestors_of(&format!("level {}", depth))
+                .unwrap()
+                .len(),
+            depth as usize
+        );
 
-        map.insert(caps[1].to_string(), v);
+        assert!(BagRules::parse(&lines(
+            "a red bags contain 1 b red bag.
+             b red bags contain 2 c red bags.
+             c red bags contain 1 a red bag."
+        ))
+        .unwrap_err()
+        .contains("a red -> b red -> c red -> a red"));
+        assert!(BagRules::parse(&lines("a red bags contain 1 a red bag.")).is_err());
+        assert!(BagRules::parse(&lines("a red bags contain 1 b red bag.")).is_err()); // No rule for b red
+        assert!(BagRules::parse(&lines("a red bags contain some b red bags.")).is_err());
+        assert!(BagRules::parse(&lines("a red bags hold no other bags.")).is_err());
     }
 
+    let rules = BagRules::from_file("input.txt").unwrap();
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", rules.to_dot());
+        return;
+    }
     {
         // Part 1
-        let mut count = 0;
-        for (k, _) in &map {
-            if contains_my_bag("shiny gold", &k, &map) {
-                // println!(" -> My bag found!");
-                count += 1;
-            }
-        }
-
-        println!("Part 1: {}", count);
-        // println!("Map is: \n{:?}", map);
+        println!(
+            "Part 1: {}",
+            rules.ancestors_of("shiny gold").unwrap().len()
+        );
     }
-    println!("Part 2: {}", count_sub_bags("shiny gold", &map, 0));
+    println!("Part 2: {}", rules.total_contents("shiny gold").unwrap());
 }
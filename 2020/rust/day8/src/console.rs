@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Instr {
+    Acc(isize),
+    Jmp(isize),
+    Nop(isize),
+}
+
+impl Instr {
+    // Parses `acc +1'
+    pub fn parse(line: &str) -> Result<Instr, String> {
+        let mut splits = line.split_whitespace();
+        let (op, arg) = match (splits.next(), splits.next(), splits.next()) {
+            (Some(op), Some(arg), None) => (op, arg),
+            _ => return Err(format!("Expected `op arg', got `{}'", line)),
+        };
+        let arg = arg
+            .strip_prefix('+')
+            .unwrap_or(arg)
+            .parse::<isize>()
+            .map_err(|_| format!("Bad argument in `{}'", line))?;
+        match op {
+            "acc" => Ok(Instr::Acc(arg)),
+            "jmp" => Ok(Instr::Jmp(arg)),
+            "nop" => Ok(Instr::Nop(arg)),
+            _ => Err(format!("Unknown operation in `{}'", line)),
+        }
+    }
+
+    // A jmp as a nop and the other way around, acc can not be changed
+    pub fn flipped(self) -> Option<Instr> {
+        match self {
+            Instr::Acc(_) => None,
+            Instr::Jmp(arg) => Some(Instr::Nop(arg)),
+            Instr::Nop(arg) => Some(Instr::Jmp(arg)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Outcome {
+    Terminated(isize),                     // Ran to just past the last instruction
+    Looped { acc: isize, pc: usize },      // About to run `pc' a second time
+    OutOfBounds { acc: isize, pc: isize }, // Jumped somewhere else outside the program
+}
+
+// One executed instruction, with the accumulator before it ran
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Step {
+    pub pc: usize,
+    pub instr: Instr,
+    pub acc: isize,
+}
+
+pub struct Console {
+    pub program: Vec<Instr>,
+}
+
+impl Console {
+    pub fn parse(lines: &[String]) -> Result<Console, String> {
+        let program = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Instr::parse(line))
+            .collect::<Result<Vec<Instr>, String>>()?;
+        Ok(Console { program })
+    }
+
+    pub fn from_file(file: &str) -> Result<Console, String> {
+        let f = File::open(file).map_err(|e| format!("Unable to open {}: {}", file, e))?;
+        let lines: Result<Vec<String>, _> = BufReader::new(f).lines().collect();
+        Console::parse(&lines.map_err(|e| format!("Unable to read {}: {}", file, e))?)
+    }
+
+    fn instr(&self, pc: usize, flip: Option<usize>) -> Instr {
+        let instr = self.program[pc];
+        match flip {
+            Some(i) if i == pc => instr.flipped().unwrap_or(instr),
+            _ => instr,
+        }
+    }
+
+    // Where `instr' at `pc' continues
+    fn next_pc(pc: usize, instr: Instr) -> isize {
+        match instr {
+            Instr::Jmp(arg) => pc as isize + arg,
+            _ => pc as isize + 1,
+        }
+    }
+
+    // Runs with the instruction at `flip' flipped, pushing every step to `trace' if given
+    fn execute(&self, flip: Option<usize>, mut trace: Option<&mut Vec<Step>>) -> Outcome {
+        let mut seen = vec![false; self.program.len()];
+        let mut acc = 0;
+        let mut pc: isize = 0;
+        loop {
+            if pc == self.program.len() as isize {
+                return Outcome::Terminated(acc);
+            }
+            if pc < 0 || pc > self.program.len() as isize {
+                return Outcome::OutOfBounds { acc, pc };
+            }
+            let at = pc as usize;
+            if seen[at] {
+                return Outcome::Looped { acc, pc: at };
+            }
+            seen[at] = true;
+            let instr = self.instr(at, flip);
+            if let Some(trace) = trace.as_mut() {
+                trace.push(Step { pc: at, instr, acc });
+            }
+            if let Instr::Acc(arg) = instr {
+                acc += arg;
+            }
+            pc = Console::next_pc(at, instr);
+        }
+    }
+
+    pub fn run(&self) -> Outcome {
+        self.execute(None, None)
+    }
+
+    pub fn run_flipped(&self, flip: usize) -> Outcome {
+        self.execute(Some(flip), None)
+    }
+
+    pub fn run_traced(&self) -> (Outcome, Vec<Step>) {
+        let mut trace = Vec::new();
+        let outcome = self.execute(None, Some(&mut trace));
+        (outcome, trace)
+    }
+
+    // Flips every jmp and nop in turn until the program terminates. The index flipped and the
+    // accumulator at the end.
+    pub fn repair_brute_force(&self) -> Option<(usize, isize)> {
+        (0..self.program.len())
+            .filter(|i| self.program[*i].flipped().is_some())
+            .find_map(|i| match self.run_flipped(i) {
+                Outcome::Terminated(acc) => Some((i, acc)),
+                _ => None,
+            })
+    }
+
+    // Instructions that run into the end of the program unchanged. Every instruction has one
+    // successor, so walking the edges backwards from the end finds them all in linear time.
+    pub fn reaches_end(&self) -> Vec<bool> {
+        let len = self.program.len();
+        let mut preds: Vec<Vec<usize>> = vec![Vec::new(); len + 1];
+        for (pc, instr) in self.program.iter().enumerate() {
+            let next = Console::next_pc(pc, *instr);
+            if next >= 0 && next <= len as isize {
+                preds[next as usize].push(pc);
+            }
+        }
+        let mut reaches = vec![false; len + 1];
+        reaches[len] = true;
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        queue.push_back(len);
+        while let Some(pc) = queue.pop_front() {
+            for pred in &preds[pc] {
+                if !reaches[*pred] {
+                    reaches[*pred] = true;
+                    queue.push_back(*pred);
+                }
+            }
+        }
+        reaches
+    }
+
+    // Like `repair_brute_force', but only the instructions the broken program actually runs are
+    // candidates, and the fix is the first one whose flipped successor reaches the end. This is
+    // one pass over the program plus one run to get the accumulator.
+    pub fn repair(&self) -> Option<(usize, isize)> {
+        let reaches = self.reaches_end();
+        let (_, trace) = self.run_traced();
+        trace.iter().find_map(|step| {
+            let next = Console::next_pc(step.pc, step.instr.flipped()?);
+            if next < 0 || next as usize >= reaches.len() || !reaches[next as usize] {
+                return None;
+            }
+            match self.run_flipped(step.pc) {
+                Outcome::Terminated(acc) => Some((step.pc, acc)),
+                _ => None,
+            }
+        })
+    }
+}
@@ -1,113 +1,64 @@
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+pub mod console;
+use console::{Console, Outcome};
 
+fn lines(s: &str) -> Vec<String> {
+    s.split(',').map(|l| l.trim().to_string()).collect()
+}
+
+// Usage: day8 [--trace]
 fn main() {
-    let re = Regex::new(r"^(acc|jmp|nop) ([+-][0-9]+)").unwrap();
-    let f = File::open("input.txt").expect("Unable to open file");
-    let f = BufReader::new(f);
+    {
+        // Tests
+        let example = Console::parse(&lines(
+            "nop +0, acc +1, jmp +4, acc +3, jmp -3, acc -99, acc +1, jmp -4, acc +6",
+        ))
+        .unwrap();
+        assert_eq!(example.run(), Outcome::Looped { acc: 5, pc: 1 });
+        let (_, trace) = example.run_traced();
+        let pcs: Vec<usize> = trace.iter().map(|step| step.pc).collect();
+        assert_eq!(pcs, vec![0, 1, 2, 6, 7, 3, 4]);
+        assert_eq!(example.run_flipped(7), Outcome::Terminated(8));
+        assert_eq!(example.run_flipped(0), Outcome::Looped { acc: 0, pc: 0 });
+        assert_eq!(example.repair_brute_force(), Some((7, 8)));
+        assert_eq!(example.repair(), Some((7, 8)));
+        let reaches = example.reaches_end();
+        assert_eq!(
+            reaches,
+            vec![false, false, false, false, false, false, false, false, true, true]
+        );
 
-    let mut v = Vec::new();
-    for line in f.lines() {
-        let line = line.expect("Unable to read line");
-        // println!("{}", line);
-        let caps = re.captures(&line).unwrap();
-        v.push((caps[1].to_string(), caps[2].parse::<isize>().unwrap(), 0));
+        let jumps_out = Console::parse(&lines("nop +0, jmp +5")).unwrap();
+        assert_eq!(jumps_out.run(), Outcome::OutOfBounds { acc: 0, pc: 6 });
+        assert_eq!(jumps_out.repair(), Some((1, 0)));
+        assert_eq!(jumps_out.repair_brute_force(), jumps_out.repair());
+        assert!(Console::parse(&lines("acc +1, mul +2")).is_err());
+        assert!(Console::parse(&lines("acc x")).is_err());
     }
-    let v = v;
-    let len = v.len();
 
+    let console = Console::from_file("input.txt").unwrap();
     {
         // Part 1
-        let mut acc = 0;
-        let mut pc: isize = 0;
-        let mut v_copy = v.clone();
-
-        loop {
-            // println!("acc: {}, pc: {}\n{:?}", acc, pc, v);
-            let (op, arg, num) = &mut v_copy[pc as usize];
-            if *num > 0 {
-                break;
+        let (outcome, trace) = console.run_traced();
+        if std::env::args().any(|arg| arg == "--trace") {
+            for step in &trace {
+                println!("{:4}: {:?}\tacc = {}", step.pc, step.instr, step.acc);
             }
-            *num += 1;
-            match op.as_str() {
-                "acc" => {
-                    acc += *arg;
-                    pc += 1;
-                }
-                "jmp" => {
-                    pc += *arg;
-                }
-                _ => {
-                    pc += 1;
-                }
+            println!("{:?}", outcome);
+        }
+        match outcome {
+            Outcome::Looped { acc, pc } => {
+                println!("Loops back to instruction {}", pc);
+                println!("Part 1: {}", acc);
             }
+            _ => panic!("Expected the program to loop, got {:?}", outcome),
         }
-        // println!("acc: {}, pc: {}\n{:?}", acc, pc, v);
-        println!("Part 1: {}", acc);
     }
     {
         // Part 2
-        let mut acc = 0;
-        for (i, _) in v.iter().enumerate() {
-            if v[i].0.as_str() == "acc" {
-                continue;
-            }
-            let mut v_copy = v.clone();
-
-            if v_copy[i].0.as_str() == "nop" {
-                v_copy[i].0 = "jmp".to_string();
-            } else {
-                v_copy[i].0 = "nop".to_string();
-            }
-
-            acc = 0;
-            let mut pc: isize = 0;
-
-            // println!("start i: {}", i);
-            loop {
-                if pc >= len as isize {
-                    // println!("pc break @ acc: {}, pc: {}\n{:?}", acc, pc, v_copy);
-                    break;
-                }
-                // let inner_v = v_copy.clone();
-                let (op, arg, num) = &mut v_copy[pc as usize];
-                // println!(
-                //     "acc: {}, pc: {} ({:?})\n{:?}",
-                //     &acc,
-                //     &pc,
-                //     (&op, &arg, &num),
-                //     &inner_v
-                // );
-                if *num > 0 {
-                    // println!(
-                    //     "num break @ acc: {}, pc: {} ({:?})\n{:?}",
-                    //     &acc,
-                    //     &pc,
-                    //     (&op, &arg, &num),
-                    //     &inner_v
-                    // );
-                    break;
-                }
-                *num += 1;
-                match op.as_str() {
-                    "acc" => {
-                        acc += *arg;
-                        pc += 1;
-                    }
-                    "jmp" => {
-                        pc += *arg;
-                    }
-                    _ => {
-                        pc += 1;
-                    }
-                }
-            }
-
-            if pc >= v.len() as isize {
-                break;
-            }
-        }
+        let (index, acc) = console
+            .repair()
+            .expect("No single flip repairs the program");
+        println!("Flipping instruction {} repairs the program", index);
         println!("Part 2: {}", acc);
     }
 }
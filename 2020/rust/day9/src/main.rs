@@ -1,70 +1,53 @@
-use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
+
+pub mod xmas;
+use xmas::{find_weakness, first_invalid, Analyser, Validity};
 
 const CIPHER_SIZE: usize = 25;
 
-fn main() {
-    let f = File::open("input.txt").expect("Unable to open file");
-    let f = BufReader::new(f);
-    let mut vec = VecDeque::new();
-    for line in f.lines() {
-        let line = line.expect("Unable to read line");
-        let entry = line.parse::<usize>().expect("Unable to parse line");
-        vec.push_back(entry);
-    }
-    let vec = vec;
+fn open(file: &str) -> BufReader<File> {
+    BufReader::new(File::open(file).expect("Unable to open file"))
+}
 
-    let mut part1 = 0;
-    {
-        // Part 1
-        let mut vec1 = VecDeque::new();
-        for entry in &vec {
-            if vec1.len() == CIPHER_SIZE {
-                let mut found = false;
-                'outer: for (i, num1) in vec1.iter().enumerate() {
-                    for (j, num2) in vec1.iter().enumerate() {
-                        if (i != j) && (num1 + num2) == *entry {
-                            found = true;
-                            break 'outer;
-                        }
-                    }
-                }
-                if found == false {
-                    part1 = *entry;
-                    break;
-                }
-            }
-            vec1.push_back(*entry);
-            if vec1.len() > CIPHER_SIZE {
-                vec1.pop_front();
-            }
-        }
-        println!("Part 1: {}", part1);
-    }
-    let part1 = part1;
+fn main() {
     {
-        // Part 2
-        let mut out: Vec<usize> = Vec::new();
-        let mut found = false;
-        for i1 in 0..vec.len() {
-            out.clear();
-            for num2 in vec.iter().skip(i1) {
-                out.push(*num2);
-                let len = out.len();
-                let sum = out.iter().sum::<usize>(); // Why is turbo fish required??
-                if len > 1 && sum == part1 {
-                    found = true;
-                    break;
-                } else if sum > part1 {
-                    break;
-                }
-            }
-            if found {
-                break;
-            }
+        // Tests
+        assert_eq!(first_invalid(open("test1.txt"), 5), Ok(Some(127)));
+        assert_eq!(find_weakness(open("test1.txt"), 127), Ok(Some(62)));
+
+        let mut analyser = Analyser::new(CIPHER_SIZE);
+        for n in std::iter::once(20).chain((1..=25).filter(|n| *n != 20)) {
+            assert_eq!(analyser.push(n), Validity::Preamble);
         }
-        out.sort();
-        println!("Part 2: {}", out.first().unwrap() + out.last().unwrap());
+        assert!(analyser.is_sum(26));
+        assert!(analyser.is_sum(49));
+        assert!(!analyser.is_sum(100));
+        assert!(!analyser.is_sum(50)); // 25 is only there once
+        assert_eq!(analyser.push(45), Validity::Valid); // 20 leaves the window
+        assert!(analyser.is_sum(26));
+        assert!(!analyser.is_sum(65));
+        assert!(analyser.is_sum(64));
+        assert!(analyser.is_sum(66));
+
+        let mut analyser = Analyser::new(2);
+        analyser.push(3);
+        analyser.push(3);
+        assert_eq!(analyser.push(6), Validity::Valid); // The same number in two places
+        assert_eq!(analyser.push(100), Validity::Invalid);
+
+        assert!(first_invalid("1\n2\nx\n".as_bytes(), 2).is_err());
+        assert_eq!(first_invalid("1\n2\n3\n".as_bytes(), 2), Ok(None));
+        assert_eq!(find_weakness("5\n".as_bytes(), 5), Ok(None)); // A range needs two numbers
+        assert_eq!(find_weakness("9\n0\n5\n".as_bytes(), 5), Ok(Some(5)));
     }
+
+    let part1 = first_invalid(open("input.txt"), CIPHER_SIZE)
+        .unwrap()
+        .expect("Every number is valid");
+    println!("Part 1: {}", part1);
+    let part2 = find_weakness(open("input.txt"), part1)
+        .unwrap()
+        .expect("No range adds up to the invalid number");
+    println!("Part 2: {}", part2);
 }
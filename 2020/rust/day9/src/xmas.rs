@@ -0,0 +1,134 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
+
+// One number per line, read as they are needed
+pub fn numbers<R: BufRead>(reader: R) -> impl Iterator<Item = Result<u64, String>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line.map_err(|e| format!("Unable to read line {}: {}", i + 1, e))?;
+            line.trim()
+                .parse::<u64>()
+                .map_err(|_| format!("Line {}: `{}' is not a number", i + 1, line))
+        })
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Validity {
+    Preamble, // Part of the first window, nothing to check against
+    Valid,
+    Invalid,
+}
+
+// The last `preamble' numbers as a multiset, so checking a number is one lookup per distinct
+// number in the window
+pub struct Analyser {
+    preamble: usize,
+    window: VecDeque<u64>,
+    counts: HashMap<u64, usize>, // Number -> times it is in the window
+}
+
+impl Analyser {
+    pub fn new(preamble: usize) -> Analyser {
+        Analyser {
+            preamble,
+            window: VecDeque::with_capacity(preamble + 1),
+            counts: HashMap::new(),
+        }
+    }
+
+    // Whether two numbers from different places in the window add up to `n'
+    pub fn is_sum(&self, n: u64) -> bool {
+        self.counts.iter().any(|(a, count)| {
+            if *a > n {
+                return false;
+            }
+            let b = n - a;
+            match self.counts.get(&b) {
+                Some(_) if b != *a => true,
+                _ => b == *a && *count >= 2,
+            }
+        })
+    }
+
+    pub fn push(&mut self, n: u64) -> Validity {
+        let validity = if self.window.len() < self.preamble {
+            Validity::Preamble
+        } else if self.is_sum(n) {
+            Validity::Valid
+        } else {
+            Validity::Invalid
+        };
+        self.window.push_back(n);
+        *self.counts.entry(n).or_insert(0) += 1;
+        if self.window.len() > self.preamble {
+            let old = self.window.pop_front().unwrap();
+            let count = self.counts.get_mut(&old).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&old);
+            }
+        }
+        validity
+    }
+}
+
+// The first number that is not the sum of two of the `preamble' numbers before it
+pub fn first_invalid<R: BufRead>(reader: R, preamble: usize) -> Result<Option<u64>, String> {
+    let mut analyser = Analyser::new(preamble);
+    for n in numbers(reader) {
+        let n = n?;
+        if analyser.push(n) == Validity::Invalid {
+            return Ok(Some(n));
+        }
+    }
+    Ok(None)
+}
+
+// A window of consecutive numbers with the sum kept up to date. With no negative numbers, a window
+// summing to more than the target can only get there again by dropping its first numbers, so
+// every number enters and leaves once.
+pub struct RangeFinder {
+    target: u64,
+    window: VecDeque<u64>,
+    sum: u64,
+}
+
+impl RangeFinder {
+    pub fn new(target: u64) -> RangeFinder {
+        RangeFinder {
+            target,
+            window: VecDeque::new(),
+            sum: 0,
+        }
+    }
+
+    // The smallest and the largest number of a range of at least two ending at `n' that adds
+    // up to the target
+    pub fn push(&mut self, n: u64) -> Option<(u64, u64)> {
+        self.window.push_back(n);
+        self.sum += n;
+        while self.sum > self.target {
+            self.sum -= self.window.pop_front().unwrap();
+        }
+        if self.sum == self.target && self.window.len() >= 2 {
+            let min = *self.window.iter().min().unwrap();
+            let max = *self.window.iter().max().unwrap();
+            return Some((min, max));
+        }
+        None
+    }
+}
+
+// The smallest plus the largest number of the first contiguous range that adds up to `target'
+pub fn find_weakness<R: BufRead>(reader: R, target: u64) -> Result<Option<u64>, String> {
+    let mut finder = RangeFinder::new(target);
+    for n in numbers(reader) {
+        if let Some((min, max)) = finder.push(n?) {
+            return Ok(Some(min + max));
+        }
+    }
+    Ok(None)
+}